@@ -0,0 +1,59 @@
+/* C bindings of the QR code generator, link with -lqr_gen_ffi */
+#ifndef QR_GEN_H
+#define QR_GEN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* return values of qr_encode, the same numbers as the exit codes of the command line
+ * (see qr-gen -h for the rest) */
+#define QR_OK 0
+#define QR_INVALID_ARGUMENT 1       /* a required pointer is null */
+#define QR_INVALID_OPTION 2         /* an option is out of range */
+#define QR_EMPTY_INPUT 5            /* no bytes to encode */
+#define QR_INPUT_TOO_LONG 6         /* more characters than any QR code can hold */
+#define QR_NO_VERSION_FITS 7        /* the data does not fit the allowed versions */
+#define QR_INVALID_NUMERIC 8        /* a numeric segment holds something other than digits */
+#define QR_UNENCODABLE_KANJI 9
+#define QR_INVALID_MODE 10          /* an encoding mode outside of 0 to 4 */
+#define QR_UNENCODABLE_CHARACTER 15
+#define QR_BUFFER_TOO_SMALL 17      /* a caller-supplied buffer cannot hold the symbol */
+#define QR_UNREPRESENTABLE_CHARACTER 18 /* a character is not part of the byte mode charset */
+#define QR_CAPACITY_EXCEEDED 19     /* the encoded data overflowed the symbol (a bug, never a truncated symbol) */
+
+typedef struct QrCodeOptions {
+    uint8_t ecc;         /* 0 = low, 1 = medium, 2 = quartile, 3 = high */
+    uint8_t min_version; /* 1 to 40 */
+    uint8_t max_version; /* 1 to 40 */
+    int8_t mask;         /* forced mask pattern (0-7), -1 to choose the least penalty */
+    uint8_t force_bytes; /* non-zero to put the whole input into a single byte segment */
+} QrCodeOptions;
+
+typedef struct QrCodeMatrix {
+    uint8_t version;
+    uint8_t ecc;
+    uint8_t mask;
+    size_t size;      /* modules on each side, quiet zone excluded */
+    uint8_t *modules; /* size * size modules row by row from the top left, 1 = dark */
+} QrCodeMatrix;
+
+/* quartile, versions 1 to 40, automatic mask */
+QrCodeOptions qr_default_options(void);
+
+/* encodes len bytes (split into segments if they are UTF-8 text), options may be NULL for the
+ * defaults. On QR_OK the matrix must be released with qr_free, on failure it is left empty. */
+int32_t qr_encode(const uint8_t *bytes, size_t len, const QrCodeOptions *options,
+                  QrCodeMatrix *out_matrix);
+
+/* releases the modules of a matrix filled by qr_encode, freeing it twice is harmless */
+void qr_free(QrCodeMatrix *matrix);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,169 @@
+//! C bindings of the generator, declared in include/qr_gen.h
+
+use std::{panic, ptr, slice};
+
+use qr_gen::{ECCLevel, QRCode, QrError, QrOptions};
+
+/// returned on success, every other return value is the `QrError::code` of the failure
+pub const QR_OK: i32 = 0;
+/// a required pointer is null (or the generator panicked)
+pub const QR_INVALID_ARGUMENT: i32 = 1;
+
+/// what `QrOptions` exposes to C, zeroed fields are not defaults: use `qr_default_options`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct QrCodeOptions {
+    /// 0 = low, 1 = medium, 2 = quartile, 3 = high
+    pub ecc: u8,
+    pub min_version: u8,
+    pub max_version: u8,
+    /// forced mask pattern (0-7), -1 to choose the least penalty
+    pub mask: i8,
+    /// non-zero to put the whole input into a single byte segment
+    pub force_bytes: u8,
+}
+
+/// a symbol returned by `qr_encode`, owned by the library until `qr_free`
+#[repr(C)]
+#[derive(Debug)]
+pub struct QrCodeMatrix {
+    pub version: u8,
+    pub ecc: u8,
+    pub mask: u8,
+    /// number of modules on each side (quiet zone excluded)
+    pub size: usize,
+    /// `size * size` modules row by row from the top left, 1 = dark and 0 = light
+    pub modules: *mut u8,
+}
+
+impl QrCodeMatrix {
+    const EMPTY: QrCodeMatrix = QrCodeMatrix {
+        version: 0,
+        ecc: 0,
+        mask: 0,
+        size: 0,
+        modules: ptr::null_mut(),
+    };
+
+    fn new(qr_code: &QRCode) -> Self {
+        let size = qr_code.size();
+        let modules = (0..size * size)
+            .map(|idx| qr_code.is_dark(idx % size, idx / size) as u8)
+            .collect::<Box<[u8]>>();
+
+        Self {
+            version: qr_code.version(),
+            ecc: qr_code.ecc_level() as u8,
+            mask: qr_code.mask(),
+            size,
+            modules: Box::into_raw(modules) as *mut u8,
+        }
+    }
+}
+
+fn options(options: &QrCodeOptions) -> Result<QrOptions, QrError> {
+    let mut builder = QrOptions::builder();
+    builder
+        .ecc(match options.ecc {
+            0 => ECCLevel::Low,
+            1 => ECCLevel::Medium,
+            2 => ECCLevel::Quartile,
+            3 => ECCLevel::High,
+            ecc => {
+                return Err(QrError::InvalidOption(format!(
+                    "error correction must be 0 to 3 but given '{}'.",
+                    ecc
+                )));
+            }
+        })
+        .min_version(options.min_version)
+        .max_version(options.max_version)
+        .force_bytes(options.force_bytes != 0);
+    if options.mask >= 0 {
+        builder.mask(options.mask as u8);
+    }
+
+    builder.build()
+}
+
+//text is split into segments like on the command line, anything else is a single byte segment
+fn encode(data: &[u8], options: &QrOptions) -> Result<QRCode, QrError> {
+    match std::str::from_utf8(data) {
+        Ok(text) => qr_gen::encode(text, options),
+        Err(_) => qr_gen::encode_bytes(data, options),
+    }
+}
+
+/// the options the command line starts from (quartile, versions 1 to 40, automatic mask)
+#[no_mangle]
+pub extern "C" fn qr_default_options() -> QrCodeOptions {
+    let defaults = QrOptions::default();
+    QrCodeOptions {
+        ecc: defaults.ecc() as u8,
+        min_version: defaults.min_version(),
+        max_version: defaults.max_version(),
+        mask: -1,
+        force_bytes: defaults.force_bytes() as u8,
+    }
+}
+
+/// encodes `len` bytes at `bytes` into `out_matrix`, returning `QR_OK` or an error code
+///
+/// # Safety
+/// `bytes` must point to `len` readable bytes (it may be null if `len` is 0), `options` must be
+/// null (for the defaults) or point to valid options, and `out_matrix` must be writable.
+/// On success the matrix has to be released with `qr_free`, on failure it is left empty.
+#[no_mangle]
+pub unsafe extern "C" fn qr_encode(
+    bytes: *const u8,
+    len: usize,
+    options: *const QrCodeOptions,
+    out_matrix: *mut QrCodeMatrix,
+) -> i32 {
+    if out_matrix.is_null() || (bytes.is_null() && len > 0) {
+        return QR_INVALID_ARGUMENT;
+    }
+    out_matrix.write(QrCodeMatrix::EMPTY);
+
+    let data = if len == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(bytes, len)
+    };
+    let options = if options.is_null() {
+        qr_default_options()
+    } else {
+        *options
+    };
+
+    //a panic must not unwind into C
+    let result = panic::catch_unwind(|| encode(data, &self::options(&options)?));
+    match result {
+        Ok(Ok(qr_code)) => {
+            out_matrix.write(QrCodeMatrix::new(&qr_code));
+            QR_OK
+        }
+        Ok(Err(e)) => e.code(),
+        Err(_) => QR_INVALID_ARGUMENT,
+    }
+}
+
+/// releases the modules of a matrix filled by `qr_encode`, freeing it twice is harmless
+///
+/// # Safety
+/// `matrix` must be null or point to a matrix written by `qr_encode` and not modified since.
+#[no_mangle]
+pub unsafe extern "C" fn qr_free(matrix: *mut QrCodeMatrix) {
+    let Some(matrix) = matrix.as_mut() else {
+        return;
+    };
+
+    if !matrix.modules.is_null() {
+        let len = matrix.size * matrix.size;
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            matrix.modules,
+            len,
+        )));
+    }
+    *matrix = QrCodeMatrix::EMPTY;
+}
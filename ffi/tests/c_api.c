@@ -0,0 +1,64 @@
+/* exercises include/qr_gen.h, run by tests/c_api.rs */
+#include <stdio.h>
+#include <string.h>
+
+#include "qr_gen.h"
+
+#define CHECK(cond)                                                    \
+    do {                                                               \
+        if (!(cond)) {                                                 \
+            fprintf(stderr, "%s:%d: failed: %s\n", __FILE__, __LINE__, #cond); \
+            return 1;                                                  \
+        }                                                              \
+    } while (0)
+
+static int dark(const QrCodeMatrix *matrix, size_t x, size_t y) {
+    return matrix->modules[y * matrix->size + x];
+}
+
+int main(void) {
+    const char *text = "HELLO WORLD";
+    QrCodeMatrix matrix;
+
+    CHECK(qr_encode((const uint8_t *)text, strlen(text), NULL, &matrix) == QR_OK);
+    CHECK(matrix.version == 1);
+    CHECK(matrix.size == 21);
+    CHECK(matrix.ecc == 2);
+    CHECK(matrix.mask <= 7);
+    /* the top left finder pattern and its separator */
+    for (size_t i = 0; i < 7; i++) {
+        CHECK(dark(&matrix, i, 0) && dark(&matrix, 0, i) && dark(&matrix, i, 6));
+        CHECK(!dark(&matrix, 7, i) && !dark(&matrix, i, 7));
+    }
+    CHECK(dark(&matrix, 3, 3) && !dark(&matrix, 1, 1));
+    qr_free(&matrix);
+    CHECK(matrix.modules == NULL);
+    qr_free(&matrix);
+
+    QrCodeOptions options = qr_default_options();
+    options.ecc = 3;
+    options.min_version = 5;
+    options.mask = 4;
+    const uint8_t binary[] = {0xDE, 0xAD, 0xBE, 0xEF, 0x00, 0xFF};
+    CHECK(qr_encode(binary, sizeof binary, &options, &matrix) == QR_OK);
+    CHECK(matrix.version == 5 && matrix.size == 37 && matrix.ecc == 3 && matrix.mask == 4);
+    qr_free(&matrix);
+
+    CHECK(qr_encode(NULL, 0, NULL, &matrix) == QR_EMPTY_INPUT);
+    CHECK(matrix.modules == NULL);
+    CHECK(qr_encode(NULL, 4, NULL, &matrix) == QR_INVALID_ARGUMENT);
+    CHECK(qr_encode((const uint8_t *)text, strlen(text), NULL, NULL) == QR_INVALID_ARGUMENT);
+
+    options = qr_default_options();
+    options.ecc = 4;
+    CHECK(qr_encode((const uint8_t *)text, strlen(text), &options, &matrix) == QR_INVALID_OPTION);
+
+    options = qr_default_options();
+    options.max_version = 1;
+    const char *long_text = "this sentence is far too long for a version 1 symbol";
+    CHECK(qr_encode((const uint8_t *)long_text, strlen(long_text), &options, &matrix) ==
+          QR_NO_VERSION_FITS);
+
+    puts("ok");
+    return 0;
+}
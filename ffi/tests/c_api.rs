@@ -0,0 +1,97 @@
+use std::{collections::HashMap, env, path::PathBuf, process::Command};
+
+use qr_gen::{Charset, Mode, QrError};
+use qr_gen_ffi::{QR_INVALID_ARGUMENT, QR_OK};
+
+//builds tests/c_api.c against include/qr_gen.h and the cdylib, then runs it
+#[test]
+fn c_program() {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    //cargo test only builds the rlib, the cdylib lands next to target/<profile>/deps
+    let mut build = Command::new(env!("CARGO"));
+    build.args(["build", "-p", "qr-gen-ffi", "--lib"]);
+    if !cfg!(debug_assertions) {
+        build.arg("--release");
+    }
+    assert!(
+        build.status().unwrap().success(),
+        "the cdylib did not build"
+    );
+
+    let exe = env::current_exe().unwrap();
+    let lib_dir = exe.parent().and_then(|deps| deps.parent()).unwrap();
+    let program = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("c_api");
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| String::from("cc")))
+        .arg(manifest.join("tests/c_api.c"))
+        .args(["-Wall", "-Werror", "-I"])
+        .arg(manifest.join("include"))
+        .arg("-L")
+        .arg(lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .args(["-lqr_gen_ffi", "-o"])
+        .arg(&program)
+        .status()
+        .expect("a C compiler is needed to build tests/c_api.c");
+    assert!(status.success(), "tests/c_api.c did not compile");
+
+    let output = Command::new(&program).output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+//every QrError qr_encode can return has a define in include/qr_gen.h, holding its `QrError::code`
+#[test]
+fn header_error_codes() {
+    let header = include_str!("../include/qr_gen.h");
+    let defines = header
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.strip_prefix("#define QR_")?.split_whitespace();
+            Some((words.next()?, words.next()?.parse::<i32>().ok()?))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let errors = [
+        ("INVALID_OPTION", QrError::InvalidOption(String::new())),
+        ("EMPTY_INPUT", QrError::EmptyInput),
+        ("INPUT_TOO_LONG", QrError::InputTooLong),
+        ("NO_VERSION_FITS", QrError::NoVersionFits),
+        ("INVALID_NUMERIC", QrError::InvalidNumeric('x')),
+        ("UNENCODABLE_KANJI", QrError::UnencodableKanji),
+        ("INVALID_MODE", QrError::InvalidMode(5)),
+        (
+            "UNENCODABLE_CHARACTER",
+            QrError::UnencodableCharacter {
+                ch: 'x',
+                mode: Mode::Numeric,
+            },
+        ),
+        ("BUFFER_TOO_SMALL", QrError::BufferTooSmall { needed: 1 }),
+        (
+            "UNREPRESENTABLE_CHARACTER",
+            QrError::UnrepresentableCharacter {
+                ch: 'x',
+                charset: Charset::Latin1,
+            },
+        ),
+        (
+            "CAPACITY_EXCEEDED",
+            QrError::CapacityExceeded {
+                bits: 1,
+                capacity: 0,
+            },
+        ),
+    ];
+
+    assert_eq!(defines.get("OK"), Some(&QR_OK));
+    assert_eq!(defines.get("INVALID_ARGUMENT"), Some(&QR_INVALID_ARGUMENT));
+    for (name, error) in &errors {
+        assert_eq!(defines.get(name), Some(&error.code()), "QR_{}", name);
+    }
+    assert_eq!(defines.len(), errors.len() + 2, "{:?}", defines);
+}
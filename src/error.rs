@@ -0,0 +1,152 @@
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+use crate::generator::Charset;
+use crate::generator::Mode;
+
+/// every way the generator, the scanner or the command line can fail
+#[derive(Debug)]
+pub enum QrError {
+    /// a command line flag or option value is invalid
+    #[cfg(feature = "std")]
+    InvalidOption(String),
+    /// the input (data file or image) could not be read
+    #[cfg(feature = "std")]
+    ReadFailure(io::Error),
+    /// the generated image or parsed text could not be written
+    #[cfg(feature = "std")]
+    WriteFailure(String),
+    /// a hex or base64 literal is malformed, or the input is not valid in its encoding
+    #[cfg(feature = "std")]
+    InvalidInput(String),
+    /// there is nothing to encode
+    EmptyInput,
+    /// more characters than any QR code (or a structured append sequence of 16) can hold
+    InputTooLong,
+    /// the data does not fit version 40 at the requested error correction level
+    NoVersionFits,
+    /// a numeric segment contains something other than digits
+    InvalidNumeric(char),
+    /// a kanji segment contains characters outside of the Shift JIS kanji range
+    UnencodableKanji,
+    /// a character of a hand-built segment does not fit the segment's mode
+    UnencodableCharacter { ch: char, mode: Mode },
+    /// a character of byte mode text is not part of the chosen charset
+    #[cfg(feature = "std")]
+    UnrepresentableCharacter { ch: char, charset: Charset },
+    /// an encoding mode outside of 0 to 4 was requested
+    InvalidMode(u8),
+    /// a GS1 element string is malformed or one of its data fields is invalid
+    #[cfg(feature = "std")]
+    InvalidGs1(String),
+    /// a serialized symbol (JSON or binary) is malformed
+    #[cfg(feature = "std")]
+    InvalidSymbol(String),
+    /// the encoded data took more bits than the version holds, the symbol is not made
+    CapacityExceeded { bits: usize, capacity: usize },
+    /// a caller-supplied buffer cannot hold the symbol, `needed` bytes are required
+    BufferTooSmall { needed: usize },
+    /// the scanned image could not be decoded
+    #[cfg(feature = "std")]
+    ImageDecode(String),
+    /// no QR code was found in the scanned image
+    NoCodeFound,
+    /// QR codes were found but none of them could be parsed
+    CodeUnreadable,
+}
+
+impl QrError {
+    /// a stable number for each failure, used as the exit code of the command line
+    /// and as the return value of the C bindings (0 being success)
+    pub fn code(&self) -> i32 {
+        match self {
+            #[cfg(feature = "std")]
+            QrError::InvalidOption(_) => 2,
+            #[cfg(feature = "std")]
+            QrError::ReadFailure(_) => 3,
+            #[cfg(feature = "std")]
+            QrError::WriteFailure(_) => 4,
+            #[cfg(feature = "std")]
+            QrError::InvalidInput(_) => 14,
+            QrError::EmptyInput => 5,
+            QrError::InputTooLong => 6,
+            QrError::NoVersionFits => 7,
+            QrError::InvalidNumeric(_) => 8,
+            QrError::UnencodableKanji => 9,
+            QrError::InvalidMode(_) => 10,
+            QrError::UnencodableCharacter { .. } => 15,
+            #[cfg(feature = "std")]
+            QrError::UnrepresentableCharacter { .. } => 18,
+            #[cfg(feature = "std")]
+            QrError::InvalidSymbol(_) => 16,
+            #[cfg(feature = "std")]
+            QrError::InvalidGs1(_) => 20,
+            QrError::BufferTooSmall { .. } => 17,
+            QrError::CapacityExceeded { .. } => 19,
+            #[cfg(feature = "std")]
+            QrError::ImageDecode(_) => 11,
+            QrError::NoCodeFound => 12,
+            QrError::CodeUnreadable => 13,
+        }
+    }
+}
+
+impl fmt::Display for QrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            QrError::InvalidOption(msg) => write!(f, "{}", msg),
+            #[cfg(feature = "std")]
+            QrError::ReadFailure(e) => write!(f, "failed to read input. {}", e),
+            #[cfg(feature = "std")]
+            QrError::WriteFailure(msg) => write!(f, "failed to write output. {}", msg),
+            #[cfg(feature = "std")]
+            QrError::InvalidInput(msg) => write!(f, "{}", msg),
+            QrError::EmptyInput => write!(f, "no characters found."),
+            QrError::InputTooLong => write!(f, "number of characters cannot fit a QR code."),
+            QrError::NoVersionFits => write!(
+                f,
+                "number of characters cannot fit a QR code. Consider choosing a lower error correction level."
+            ),
+            QrError::InvalidNumeric(ch) => write!(f, "'{}' cannot be encoded as numerals.", ch),
+            QrError::UnencodableKanji => write!(f, "cannot encode kanji."),
+            QrError::UnencodableCharacter { ch, mode } => {
+                write!(f, "'{}' cannot be encoded in {:?} mode.", ch, mode)
+            }
+            #[cfg(feature = "std")]
+            QrError::UnrepresentableCharacter { ch, charset } => {
+                write!(f, "'{}' cannot be represented in {}.", ch, charset)
+            }
+            QrError::InvalidMode(mode) => write!(f, "weird encoding mode {}.", mode),
+            #[cfg(feature = "std")]
+            QrError::InvalidGs1(msg) => write!(f, "invalid GS1 element string. {}", msg),
+            #[cfg(feature = "std")]
+            QrError::InvalidSymbol(msg) => write!(f, "malformed symbol. {}", msg),
+            QrError::CapacityExceeded { bits, capacity } => write!(
+                f,
+                "the data takes {} bits but the symbol holds {}.",
+                bits, capacity
+            ),
+            QrError::BufferTooSmall { needed } => {
+                write!(f, "buffer too small, {} bytes are needed.", needed)
+            }
+            #[cfg(feature = "std")]
+            QrError::ImageDecode(msg) => write!(f, "failed to decode image. {}", msg),
+            QrError::NoCodeFound => write!(f, "QR code not found."),
+            QrError::CodeUnreadable => write!(f, "QR code cannot be parsed."),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for QrError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            QrError::ReadFailure(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -1,114 +1,75 @@
-use std::process::exit;
-
-pub struct BitStream {
-    bytes: Vec<u8>,
-    offset: u8,
+/// writes bits into a caller-owned buffer, nothing is allocated
+/// bits that do not fit the buffer are dropped and the stream is flagged as overflowed
+pub struct BitStream<'a> {
+    bytes: &'a mut [u8],
+    size: usize,
+    overflow: bool,
 }
 
-impl BitStream {
-    pub fn new() -> Self {
+impl<'a> BitStream<'a> {
+    pub fn new(bytes: &'a mut [u8]) -> Self {
         Self {
-            bytes: Vec::new(),
-            offset: 0,
+            bytes,
+            size: 0,
+            overflow: false,
         }
     }
 
     pub fn push(&mut self, byte: u8) {
-        if self.offset == 0 {
-            self.bytes.push(byte);
-        } else {
-            let last_num = self.bytes.last_mut().unwrap();
-            *last_num |= byte >> self.offset;
-            self.bytes
-                .push((byte & (0xFF >> (8 - self.offset))) << (8 - self.offset));
-        }
+        self.push_bits(byte, 8);
     }
 
+    /// number of bits pushed so far, overflowed bits included
     pub fn size(&self) -> usize {
-        if self.offset == 0 {
-            8 * self.bytes.len()
-        } else if self.bytes.len() == 1 {
-            self.offset as usize
-        } else {
-            8 * (self.bytes.len() - 1) + self.offset as usize
-        }
+        self.size
     }
 
-    /// @param number number to push
-    /// @param size number of bits to push
-    pub fn push_bits(&mut self, number: u8, size: u8) {
-        if size > 8 {
-            eprintln!("BitStream: push_bits size, {size} > 8.");
-            exit(0);
-        }
-
-        let num = number & (0xFF >> (8 - size));
-
-        if size == 8 {
-            self.push(num);
-            return;
-        }
-
-        if self.bytes.is_empty() {
-            self.bytes.push(num << (8 - size));
-            self.offset = size;
-            return;
-        }
-
-        if self.offset + size == 8 {
-            let last_num = self.bytes.last_mut().unwrap();
-            *last_num |= num;
+    /// true if more bits were pushed than the buffer can hold
+    pub fn overflowed(&self) -> bool {
+        self.overflow
+    }
 
-            self.offset = 0;
-        } else if self.offset + size > 8 {
-            let last_num = self.bytes.last_mut().unwrap();
-            *last_num |= num >> (size - (8 - self.offset));
+    /// number of bytes touched, the last one is padded with 0s
+    pub fn len(&self) -> usize {
+        self.size.div_ceil(8).min(self.bytes.len())
+    }
 
-            self.offset = size - (8 - self.offset); //remaining size
-            self.bytes
-                .push((num & (0xFF >> (8 - self.offset))) << (8 - self.offset));
-        } else {
-            if self.offset == 0 {
-                self.bytes.push(num << (8 - size));
-                self.offset = size;
-                return;
+    fn push_bit(&mut self, bit: bool) {
+        let idx = self.size / 8;
+        let shift = 7 - self.size % 8;
+
+        match self.bytes.get_mut(idx) {
+            Some(byte) => {
+                //the buffer may hold anything, clear each byte when it is first written
+                if shift == 7 {
+                    *byte = 0;
+                }
+                *byte |= (bit as u8) << shift;
             }
+            None => self.overflow = true,
+        }
 
-            self.offset += size;
+        self.size += 1;
+    }
 
-            let last_num = self.bytes.last_mut().unwrap();
-            *last_num |= num << (8 - self.offset);
-        }
+    /// @param number number to push
+    /// @param size number of bits to push, sizes above 8 are forwarded to push_bits_big
+    pub fn push_bits(&mut self, number: u8, size: u8) {
+        self.push_bits_big(number as usize, size);
     }
 
     /// @param number number to push
     /// @param size number of bits to push
     pub fn push_bits_big(&mut self, number: usize, size: u8) {
-        if size <= 8 {
-            self.push_bits(number as u8, size);
-        } else {
-            let rem = size % 8;
-            let blocks = size / 8;
-
-            if rem != 0 {
-                self.push_bits((number >> (8 * blocks)) as u8, rem);
-            }
-
-            for i in 0..blocks {
-                self.push_bits((number >> (8 * (blocks - i - 1))) as u8, 8);
-            }
+        for i in (0..size).rev() {
+            self.push_bit((number >> i) & 1 == 1);
         }
     }
 
-    pub fn consume(self) -> (Vec<u8>, usize) {
-        let sub_size = if self.offset == 0 { 0 } else { 8 - self.offset } as usize;
-        let size = 8 * self.bytes.len() - sub_size;
-        (self.bytes, size)
-    }
-
+    #[cfg(feature = "std")]
     #[allow(dead_code)]
     pub fn debug_print(&self) {
-        for num in &self.bytes {
+        for num in &self.bytes[..self.len()] {
             print!("{num:08b} ")
         }
     }
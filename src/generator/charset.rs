@@ -0,0 +1,311 @@
+use std::fmt;
+
+#[cfg(feature = "charsets")]
+use encoding_rs::{EncoderResult, Encoding};
+
+use super::{Mode, Segment};
+use crate::error::QrError;
+
+/// size of the ECI header carrying `designator`, mode indicator included
+pub(crate) const fn eci_bits(designator: u32) -> usize {
+    4 + if designator < 1 << 7 {
+        8
+    } else if designator < 1 << 14 {
+        16
+    } else {
+        24
+    }
+}
+
+/// the character set the text of byte segments is written in, the same for the whole symbol
+///
+/// anything but `Latin1` (what scanners assume) is announced by an ECI at the start of the symbol,
+/// and anything but `Latin1` and `Utf8` needs the `charsets` feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    /// ISO-8859-1
+    Latin1,
+    /// ISO-8859-2 to ISO-8859-16 (there is no ISO-8859-12), ISO-8859-1 being `Latin1`
+    Iso8859(u8),
+    ShiftJis,
+    Windows1250,
+    Windows1251,
+    Windows1252,
+    Utf8,
+    Big5,
+    Gb18030,
+    EucKr,
+}
+
+impl Charset {
+    /// the charset named `name` (case insensitive), eg: "utf-8", "shift_jis", "iso-8859-5", "windows-1251"
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase().replace('_', "-");
+        if let Some(part) = name.strip_prefix("iso-8859-") {
+            return match part.parse::<u8>() {
+                Ok(1) => Some(Charset::Latin1),
+                Ok(part) => Some(Charset::Iso8859(part)).filter(|charset| charset.is_valid()),
+                Err(_) => None,
+            };
+        }
+
+        Some(match name.as_str() {
+            "latin1" | "latin-1" => Charset::Latin1,
+            "shift-jis" | "sjis" => Charset::ShiftJis,
+            "windows-1250" | "cp1250" => Charset::Windows1250,
+            "windows-1251" | "cp1251" => Charset::Windows1251,
+            "windows-1252" | "cp1252" => Charset::Windows1252,
+            "utf-8" | "utf8" => Charset::Utf8,
+            "big5" => Charset::Big5,
+            "gb18030" => Charset::Gb18030,
+            "euc-kr" => Charset::EucKr,
+            _ => return None,
+        })
+    }
+
+    /// the charset of the ECI designator `eci`
+    pub fn from_eci(eci: u32) -> Option<Self> {
+        Some(match eci {
+            1 | 3 => Charset::Latin1,
+            4..=13 | 15..=18 => Charset::Iso8859(eci as u8 - 2),
+            20 => Charset::ShiftJis,
+            21 => Charset::Windows1250,
+            22 => Charset::Windows1251,
+            23 => Charset::Windows1252,
+            26 => Charset::Utf8,
+            28 => Charset::Big5,
+            29 | 32 => Charset::Gb18030,
+            30 => Charset::EucKr,
+            _ => return None,
+        })
+    }
+
+    /// the ECI designator of the charset
+    pub fn eci(self) -> u32 {
+        match self {
+            Charset::Latin1 => 3,
+            Charset::Iso8859(part) => part as u32 + 2,
+            Charset::ShiftJis => 20,
+            Charset::Windows1250 => 21,
+            Charset::Windows1251 => 22,
+            Charset::Windows1252 => 23,
+            Charset::Utf8 => 26,
+            Charset::Big5 => 28,
+            //29 is "GB2312" but is what scanners understand as GB18030, 32 is barely known
+            Charset::Gb18030 => 29,
+            Charset::EucKr => 30,
+        }
+    }
+
+    /// false for ISO-8859 parts that do not exist
+    pub(crate) fn is_valid(self) -> bool {
+        match self {
+            Charset::Iso8859(part) => (2..=16).contains(&part) && part != 12,
+            _ => true,
+        }
+    }
+
+    /// true if the charset can be written without the `charsets` feature
+    pub(crate) fn is_builtin(self) -> bool {
+        matches!(self, Charset::Latin1 | Charset::Utf8)
+    }
+
+    /// the ECI written at the start of the symbol, `None` for the default charset
+    /// or if there is no byte mode text it would apply to
+    pub(crate) fn header(self, segments: &[Segment]) -> Option<u32> {
+        let text = segments
+            .iter()
+            .any(|seg| seg.mode() == Mode::Byte && seg.text().is_some());
+
+        match self {
+            Charset::Latin1 => None,
+            charset => text.then(|| charset.eci()),
+        }
+    }
+
+    /// Latin-1 if every byte segment fits it, UTF-8 otherwise
+    pub(crate) fn of(segments: &[Segment]) -> Self {
+        let latin1 = segments
+            .iter()
+            .filter(|seg| seg.mode() == Mode::Byte)
+            .filter_map(Segment::text)
+            .all(|text| text.chars().all(|ch| Charset::Latin1.accepts(ch)));
+
+        if latin1 {
+            Charset::Latin1
+        } else {
+            Charset::Utf8
+        }
+    }
+
+    //the encoding_rs table and whether its 0x80-0x9F differ from the charset
+    //(windows-1254 and windows-874 stand in for ISO-8859-9 and ISO-8859-11)
+    #[cfg(feature = "charsets")]
+    fn encoding(self) -> (&'static Encoding, bool) {
+        use encoding_rs::*;
+
+        match self {
+            Charset::Iso8859(part) => match part {
+                2 => (ISO_8859_2, false),
+                3 => (ISO_8859_3, false),
+                4 => (ISO_8859_4, false),
+                5 => (ISO_8859_5, false),
+                6 => (ISO_8859_6, false),
+                7 => (ISO_8859_7, false),
+                8 => (ISO_8859_8, false),
+                9 => (WINDOWS_1254, true),
+                10 => (ISO_8859_10, false),
+                11 => (WINDOWS_874, true),
+                13 => (ISO_8859_13, false),
+                14 => (ISO_8859_14, false),
+                15 => (ISO_8859_15, false),
+                _ => (ISO_8859_16, false),
+            },
+            Charset::ShiftJis => (SHIFT_JIS, false),
+            Charset::Windows1250 => (WINDOWS_1250, false),
+            Charset::Windows1251 => (WINDOWS_1251, false),
+            Charset::Windows1252 => (WINDOWS_1252, false),
+            Charset::Big5 => (BIG5, false),
+            Charset::Gb18030 => (GB18030, false),
+            Charset::EucKr => (EUC_KR, false),
+            //handled without tables
+            Charset::Latin1 => (WINDOWS_1252, false),
+            Charset::Utf8 => (UTF_8, false),
+        }
+    }
+
+    /// number of octets of `ch`, `None` if the charset cannot represent it
+    pub(crate) fn char_len(self, ch: char) -> Option<usize> {
+        match self {
+            //Latin-1 is the first 256 code points (encoding_rs maps its label to windows-1252)
+            Charset::Latin1 => ((ch as u32) <= 0xFF).then_some(1),
+            Charset::Utf8 => Some(ch.len_utf8()),
+            #[cfg(feature = "charsets")]
+            charset => {
+                let (encoding, c1_differs) = charset.encoding();
+                let mut buffer = [0; 4];
+                let mut out = [0; 8];
+                let (result, _, len) = encoding
+                    .new_encoder()
+                    .encode_from_utf8_without_replacement(ch.encode_utf8(&mut buffer), &mut out, true);
+
+                //some characters share their octets with others (Shift_JIS writes '¥' as the 0x5C
+                //of a backslash), only the ones scanners decode back are represented
+                let c1 = c1_differs && len == 1 && (0x80..=0x9F).contains(&out[0]);
+                let decoded = encoding.decode_without_bom_handling(&out[..len]).0;
+                let round_trip = decoded.chars().eq([ch]);
+                (result == EncoderResult::InputEmpty && !c1 && round_trip).then_some(len)
+            }
+            #[cfg(not(feature = "charsets"))]
+            _ => None,
+        }
+    }
+
+    pub(crate) fn accepts(self, ch: char) -> bool {
+        self.char_len(ch).is_some()
+    }
+
+    /// fails on the first character the charset cannot represent
+    pub(crate) fn check(self, text: &str) -> Result<(), QrError> {
+        match text.chars().find(|&ch| !self.accepts(ch)) {
+            Some(ch) => Err(QrError::UnrepresentableCharacter { ch, charset: self }),
+            None => Ok(()),
+        }
+    }
+
+    /// number of octets `text` takes in this charset (the characters it cannot represent are skipped)
+    pub(crate) fn encoded_len(self, text: &str) -> usize {
+        match self {
+            Charset::Latin1 => text.chars().count(),
+            Charset::Utf8 => text.len(),
+            charset => text.chars().filter_map(|ch| charset.char_len(ch)).sum(),
+        }
+    }
+
+    /// the octets of `text`, see `check` for the error
+    pub(crate) fn encode(self, text: &str) -> Result<Vec<u8>, QrError> {
+        self.check(text)?;
+
+        Ok(match self {
+            Charset::Latin1 => text.chars().map(|ch| ch as u8).collect(),
+            Charset::Utf8 => text.as_bytes().to_vec(),
+            #[cfg(feature = "charsets")]
+            charset => charset.encoding().0.encode(text).0.into_owned(),
+            #[cfg(not(feature = "charsets"))]
+            _ => Vec::new(),
+        })
+    }
+
+    /// the text of octets written in this charset, `None` if they hold a malformed sequence
+    pub(crate) fn decode_exact(self, octets: &[u8]) -> Option<String> {
+        match self {
+            Charset::Latin1 => Some(octets.iter().map(|&byte| byte as char).collect()),
+            Charset::Utf8 => String::from_utf8(octets.to_vec()).ok(),
+            #[cfg(feature = "charsets")]
+            charset => {
+                let (encoding, c1_differs) = charset.encoding();
+                let decode = |octets: &[u8]| {
+                    encoding
+                        .decode_without_bom_handling_and_without_replacement(octets)
+                        .map(|text| text.into_owned())
+                };
+
+                if c1_differs {
+                    //see `decode`
+                    octets
+                        .iter()
+                        .map(|&byte| match byte {
+                            0x80..=0x9F => Some(String::from(byte as char)),
+                            _ => decode(&[byte]),
+                        })
+                        .collect()
+                } else {
+                    decode(octets)
+                }
+            }
+            #[cfg(not(feature = "charsets"))]
+            _ => None,
+        }
+    }
+
+    /// the text of octets written in this charset, malformed sequences become U+FFFD
+    #[cfg(feature = "scanner")]
+    pub(crate) fn decode(self, octets: &[u8]) -> String {
+        match self {
+            Charset::Latin1 => octets.iter().map(|&byte| byte as char).collect(),
+            Charset::Utf8 => String::from_utf8_lossy(octets).into_owned(),
+            charset => {
+                let (encoding, c1_differs) = charset.encoding();
+                if c1_differs {
+                    //single byte charsets whose 0x80-0x9F are the C1 controls, not windows punctuation
+                    octets
+                        .iter()
+                        .map(|&byte| match byte {
+                            0x80..=0x9F => String::from(byte as char),
+                            _ => encoding.decode_without_bom_handling(&[byte]).0.into_owned(),
+                        })
+                        .collect()
+                } else {
+                    encoding.decode_without_bom_handling(octets).0.into_owned()
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Charset::Latin1 => write!(f, "ISO-8859-1"),
+            Charset::Iso8859(part) => write!(f, "ISO-8859-{}", part),
+            Charset::ShiftJis => write!(f, "Shift_JIS"),
+            Charset::Windows1250 => write!(f, "windows-1250"),
+            Charset::Windows1251 => write!(f, "windows-1251"),
+            Charset::Windows1252 => write!(f, "windows-1252"),
+            Charset::Utf8 => write!(f, "UTF-8"),
+            Charset::Big5 => write!(f, "Big5"),
+            Charset::Gb18030 => write!(f, "GB18030"),
+            Charset::EucKr => write!(f, "EUC-KR"),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use super::{
+    data::{block_division, qr_capacity_query},
+    ecc::ErrorCorrection,
+    ECCLevel,
+};
+
+/// number of codewords (data and error correction) of a version, the same at every level
+pub const fn total_codewords(version: u8) -> usize {
+    let (blocks, blocks_num) = block_division(version, &ECCLevel::Low);
+    blocks[0].0 * blocks_num[0] + blocks[1].0 * blocks_num[1]
+}
+
+/// pads the `used` data codewords at the start of `codewords` and appends the error correction
+/// codewords of each block behind them, in block order (see `interleaved`)
+pub fn finish(codewords: &mut [u8], used: usize, version: u8, ecc: &ECCLevel) {
+    let data_len = qr_capacity_query(ecc, version) / 8;
+    let (data, err) = codewords[..total_codewords(version)].split_at_mut(data_len);
+
+    for (idx, byte) in data[used..].iter_mut().enumerate() {
+        *byte = if idx % 2 == 0 { 0xEC } else { 0x11 };
+    }
+
+    let (blocks, blocks_num) = block_division(version, ecc);
+    let err_len = blocks[0].0 - blocks[0].1;
+    let calc = ErrorCorrection::new();
+
+    let mut data_idx = 0;
+    let mut err_idx = 0;
+    for (&(_, block_len, _), &num) in blocks.iter().zip(blocks_num.iter()) {
+        for _ in 0..num {
+            calc.calculate(
+                &data[data_idx..data_idx + block_len],
+                &mut err[err_idx..err_idx + err_len],
+            );
+            data_idx += block_len;
+            err_idx += err_len;
+        }
+    }
+}
+
+/// the codewords written by `finish` in the order they are placed in the symbol:
+/// the n-th data codeword of every block, then the n-th error correction codeword of every block
+pub fn interleaved<'a>(
+    codewords: &'a [u8],
+    version: u8,
+    ecc: &ECCLevel,
+) -> impl Iterator<Item = u8> + 'a {
+    interleaved_order(version, ecc).map(|idx| codewords[idx])
+}
+
+/// for each codeword in placement order, its index in the block order of `finish`
+pub fn interleaved_order(version: u8, ecc: &ECCLevel) -> impl Iterator<Item = usize> {
+    let (blocks, blocks_num) = block_division(version, ecc);
+    let short_len = blocks[0].1;
+    let short_num = blocks_num[0];
+    let num = blocks_num[0] + blocks_num[1];
+    let data_len = qr_capacity_query(ecc, version) / 8;
+    let err_len = blocks[0].0 - blocks[0].1;
+
+    (0..total_codewords(version)).map(move |idx| {
+        if idx >= data_len {
+            let idx = idx - data_len;
+            data_len + (idx % num) * err_len + idx / num
+        } else if idx < short_len * num {
+            //the long blocks (second group) start after the short ones
+            let block = idx % num;
+            let start = if block < short_num {
+                block * short_len
+            } else {
+                short_num * short_len + (block - short_num) * (short_len + 1)
+            };
+            start + idx / num
+        } else {
+            //the last data codeword of each long block
+            let block = short_num + idx - short_len * num;
+            short_num * short_len + (block - short_num) * (short_len + 1) + short_len
+        }
+    })
+}
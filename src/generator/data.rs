@@ -1,150 +1,96 @@
 use crate::generator::ECCLevel;
 
-const MAX_SIZE_DATA: [[usize; 40]; 4] = [
+//how many blocks of each group there are, the second group is absent in some versions (0 blocks)
+const BLOCK_COUNT: [[[usize; 2]; 40]; 4] = [
     [
-        152, 272, 440, 640, 864, 1088, 1248, 1552, 1856, 2192,
-        2592, 2960, 3424, 3688, 4184, 4712, 5176, 5768, 6360, 6888,
-        7456, 8048, 8752, 9392, 10208, 10960, 11744, 12248, 13048, 13880,
-        14744, 15640, 16568, 17528, 18448, 19472, 20528, 21616, 22496, 23648
+        [1, 0], [1, 0], [1, 0], [1, 0], [1, 0],
+        [2, 0], [2, 0], [2, 0], [2, 0], [2, 2],
+        [4, 0], [2, 2], [4, 0], [3, 1], [5, 1],
+        [5, 1], [1, 5], [5, 1], [3, 4], [3, 5],
+        [4, 4], [2, 7], [4, 5], [6, 4], [8, 4],
+        [10, 2], [8, 4], [3, 10], [7, 7], [5, 10],
+        [13, 3], [17, 0], [17, 1], [13, 6], [12, 7],
+        [6, 14], [17, 4], [4, 18], [20, 4], [19, 6]
     ],
     [
-        128, 224, 352, 512, 688, 864, 992, 1232, 1456, 1728,
-        2032, 2320, 2672, 2920, 3320, 3624, 4056, 4504, 5016, 5352,
-        5712, 6256, 6880, 7312, 8000, 8496, 9024, 9544, 10136, 10984,
-        11640, 12328, 13048, 13800, 14496, 15312, 15936, 16816, 17728, 18672
+        [1, 0], [1, 0], [1, 0], [2, 0], [2, 0],
+        [4, 0], [4, 0], [2, 2], [3, 2], [4, 1],
+        [1, 4], [6, 2], [8, 1], [4, 5], [5, 5],
+        [7, 3], [10, 1], [9, 4], [3, 11], [3, 13],
+        [17, 0], [17, 0], [4, 14], [6, 14], [8, 13],
+        [19, 4], [22, 3], [3, 23], [21, 7], [19, 10],
+        [2, 29], [10, 23], [14, 21], [14, 23], [12, 26],
+        [6, 34], [29, 14], [13, 32], [40, 7], [18, 31]
     ],
     [
-        104, 176, 272, 384, 496, 608, 704, 880, 1056, 1232,
-        1440, 1648, 1952, 2088, 2360, 2600, 2936, 3176, 3560, 3880,
-        4096, 4544, 4912, 5312, 5744, 6032, 6464, 6968, 7288, 7880,
-        8264, 8920, 9368, 9848, 10288, 10832, 11408, 12016, 12656, 13328
+        [1, 0], [1, 0], [2, 0], [2, 0], [2, 2],
+        [4, 0], [2, 4], [4, 2], [4, 4], [6, 2],
+        [4, 4], [4, 6], [8, 4], [11, 5], [5, 7],
+        [15, 2], [1, 15], [17, 1], [17, 4], [15, 5],
+        [17, 6], [7, 16], [11, 14], [11, 16], [7, 22],
+        [28, 6], [8, 26], [4, 31], [1, 37], [15, 25],
+        [42, 1], [10, 35], [29, 19], [44, 7], [39, 14],
+        [46, 10], [49, 10], [48, 14], [43, 22], [34, 34]
     ],
     [
-        72, 128, 208, 288, 368, 480, 528, 688, 800, 976,
-        1120, 1264, 1440, 1576, 1784, 2024, 2264, 2504, 2728, 3080,
-        3248, 3536, 3712, 4112, 4304, 4768, 5024, 5288, 5608, 5960,
-        6344, 6760, 7208, 7688, 7888, 8432, 8768, 9136, 9776, 10208
-    ]
+        [1, 0], [1, 0], [2, 0], [4, 0], [2, 2],
+        [4, 0], [4, 1], [4, 2], [4, 4], [6, 2],
+        [3, 8], [7, 4], [12, 4], [11, 5], [11, 7],
+        [3, 13], [2, 17], [2, 19], [9, 16], [15, 10],
+        [19, 6], [34, 0], [16, 14], [30, 2], [22, 13],
+        [33, 4], [12, 28], [11, 31], [19, 26], [23, 25],
+        [23, 28], [19, 35], [11, 46], [59, 1], [22, 41],
+        [2, 64], [24, 46], [42, 32], [10, 67], [20, 61]
+    ],
 ];
 
-pub struct BlockDivision {
-    n0: [Vec<usize>; 40],
-    n1: [Vec<usize>; 40],
-    n2: [Vec<usize>; 40],
-    n3: [Vec<usize>; 40],
-    //the third variable is unused for now as it signifies the 'error correction capacity', which is purely informational
-    ecc0: [Vec<(usize, usize, usize)>; 40],
-    ecc1: [Vec<(usize, usize, usize)>; 40],
-    ecc2: [Vec<(usize, usize, usize)>; 40],
-    ecc3: [Vec<(usize, usize, usize)>; 40],
-}
-
-impl BlockDivision {
-    pub fn new() -> Self {
-        Self {
-            n0: [
-                vec![1], vec![1], vec![1], vec![1], vec![1],
-                vec![2], vec![2], vec![2], vec![2], vec![2, 2],
-                vec![4], vec![2, 2], vec![4], vec![3, 1], vec![5, 1],
-                vec![5, 1], vec![1, 5], vec![5, 1], vec![3, 4], vec![3, 5],
-                vec![4, 4], vec![2, 7], vec![4, 5], vec![6, 4], vec![8, 4],
-                vec![10, 2], vec![8, 4], vec![3, 10], vec![7, 7], vec![5, 10],
-                vec![13, 3], vec![17], vec![17, 1], vec![13, 6], vec![12, 7],
-                vec![6, 14], vec![17, 4], vec![4, 18], vec![20, 4], vec![19, 6]
-            ],
-            n1: [
-                vec![1], vec![1], vec![1], vec![2], vec![2],
-                vec![4], vec![4], vec![2, 2], vec![3, 2], vec![4, 1],
-                vec![1, 4], vec![6, 2], vec![8, 1], vec![4, 5], vec![5, 5],
-                vec![7, 3], vec![10, 1], vec![9, 4], vec![3, 11], vec![3, 13],
-                vec![17], vec![17], vec![4, 14], vec![6, 14], vec![8, 13],
-                vec![19, 4], vec![22, 3], vec![3, 23], vec![21, 7], vec![19, 10],
-                vec![2, 29], vec![10, 23], vec![14, 21], vec![14, 23], vec![12, 26],
-                vec![6, 34], vec![29, 14], vec![13, 32], vec![40, 7], vec![18, 31]
-            ],
-            n2: [
-                vec![1], vec![1], vec![2], vec![2], vec![2, 2],
-                vec![4], vec![2, 4], vec![4, 2], vec![4, 4], vec![6, 2],
-                vec![4, 4], vec![4, 6], vec![8, 4], vec![11, 5], vec![5, 7],
-                vec![15, 2], vec![1, 15], vec![17, 1], vec![17, 4], vec![15, 5],
-                vec![17, 6], vec![7, 16], vec![11, 14], vec![11, 16], vec![7, 22],
-                vec![28, 6], vec![8, 26], vec![14, 31], vec![1, 37], vec![15, 25],
-                vec![42, 1], vec![10, 35], vec![29, 19], vec![44, 7], vec![39, 14],
-                vec![46, 10], vec![49, 10], vec![48, 14], vec![43, 22], vec![34, 34]
-            ],
-            n3: [
-                vec![1], vec![1], vec![2], vec![4], vec![2, 2],
-                vec![4], vec![4, 1], vec![4, 2], vec![4, 4], vec![6, 2],
-                vec![3, 8], vec![7, 4], vec![12, 4], vec![11, 5], vec![11, 7],
-                vec![3, 13], vec![2, 17], vec![2, 19], vec![9, 16], vec![15, 10],
-                vec![19, 6], vec![34], vec![16, 14], vec![30, 2], vec![22, 13],
-                vec![33, 4], vec![12, 28], vec![11, 31], vec![19, 26], vec![23, 25],
-                vec![23, 28], vec![19, 35], vec![11, 46], vec![59, 1], vec![22, 41],
-                vec![2, 64], vec![24, 46], vec![42, 32], vec![10, 67], vec![20, 61]
-            ],
-            ecc0: [
-                vec![(26, 19, 2)], vec![(44, 34, 4)], vec![(70, 55, 7)], vec![(100, 80, 10)], vec![(134, 108, 13)],
-                vec![(86, 68, 9)], vec![(98, 78, 10)], vec![(121, 97, 12)], vec![(146, 116, 15)], vec![(86, 68, 9), (87, 69, 9)],
-                vec![(101, 81, 10)], vec![(116, 92, 12), (117, 93, 12)], vec![(133, 107, 13)], vec![(145, 115, 15), (146, 116, 15)], vec![(109, 87, 11), (110, 88, 11)],
-                vec![(122, 98, 12), (123, 99, 12)], vec![(135, 107, 14), (136, 108, 14)], vec![(150, 120, 15), (151, 121, 15)], vec![(141, 113, 14), (142, 114, 14)], vec![(135, 107, 14), (136, 108, 14)],
-                vec![(144, 116, 14), (145, 117, 14)], vec![(139, 111, 14), (140, 112, 14)], vec![(151, 121, 15), (152, 122, 15)], vec![(147, 117, 15), (148, 118, 15)], vec![(132, 106, 13), (133, 107, 13)],
-                vec![(142, 114, 14), (143, 115, 14)], vec![(152, 122, 15), (153, 123, 15)], vec![(147, 117, 15), (148, 118, 15)], vec![(146, 116, 15), (147, 117, 15)], vec![(145, 115, 15), (146, 116, 15)],
-                vec![(145, 115, 15), (146, 116, 15)], vec![(145, 115, 15)], vec![(145, 115, 15), (146, 116, 15)], vec![(145, 115, 15), (146, 116, 15)], vec![(151, 121, 15), (152, 122, 15)],
-                vec![(151, 121, 15), (152, 122, 15)], vec![(152, 122, 15), (153, 123, 15)], vec![(152, 122, 15), (153, 123, 15)], vec![(147, 117, 15), (148, 118, 15)],  vec![(148, 118, 15), (149, 119, 15)]
-            ],
-            ecc1: [
-                vec![(26, 16, 4)], vec![(44, 28, 8)], vec![(70, 44, 13)], vec![(50, 32, 9)], vec![(67, 43, 12)],
-                vec![(43, 27, 8)], vec![(49, 31, 9)], vec![(60, 38, 11), (61, 39, 11)], vec![(58, 36, 11), (59, 37, 11)], vec![(69, 43, 13), (70, 44, 13)],
-                vec![(80, 50, 15), (81, 51, 15)], vec![(58, 36, 11), (59, 37, 11)], vec![(59, 37, 11), (60, 38, 11)], vec![(64, 40, 12), (65, 41, 12)], vec![(65, 41, 12), (66, 42, 12)],
-                vec![(73, 45, 14), (74, 46, 14)], vec![(74, 46, 14), (75, 47, 14)], vec![(69, 43, 13), (70, 44, 13)], vec![(70, 44, 13), (71, 45, 13)], vec![(67, 41, 13), (68, 42, 13)],
-                vec![(68, 42, 13)], vec![(74, 46, 14)], vec![(75, 47, 14), (76, 48, 14)], vec![(73, 45, 14), (74, 46, 14)], vec![(75, 47, 14), (76, 48, 14)],
-                vec![(74, 46, 14), (75, 47, 14)], vec![(73, 45, 14), (74, 46, 14)], vec![(73, 45, 14), (74, 46, 14)], vec![(73, 45, 14), (74, 46, 14)], vec![(75, 47, 14), (76, 48, 14)],
-                vec![(74, 46, 14), (75, 47, 14)], vec![(74, 46, 14), (75, 47, 14)], vec![(74, 46, 14), (75, 47, 14)], vec![(74, 46, 14), (75, 47, 14)], vec![(75, 47, 14), (76, 48, 14)],
-                vec![(75, 47, 14), (76, 48, 14)], vec![(74, 46, 14), (75, 47, 14)], vec![(74, 46, 14), (75, 47, 14)], vec![(75, 47, 14), (76, 48, 14)], vec![(75, 47, 14), (76, 48, 14)]
-            ],
-            ecc2: [
-                vec![(26, 13, 6)], vec![(44, 22, 11)], vec![(35, 17, 9)], vec![(50, 24, 13)], vec![(33, 15, 9), (34, 16, 9)],
-                vec![(43, 19, 12)], vec![(32, 14, 9), (33, 15, 9)], vec![(40, 18, 11), (41, 19, 11)], vec![(36, 16, 10), (37, 17, 10)], vec![(43, 19, 12), (44, 20, 12)],
-                vec![(50, 22, 14), (51, 23, 14)], vec![(46, 20, 13), (47, 21, 13)], vec![(44, 20, 12), (45, 21, 12)], vec![(36, 16, 10), (37, 17, 10)], vec![(54, 24, 15), (55, 25, 15)],
-                vec![(43, 19, 12), (44, 20, 12)], vec![(50, 22, 14), (51, 23, 14)], vec![(50, 22, 14), (51, 23, 14)], vec![(47, 21, 13), (48, 22, 13)], vec![(54, 24, 15), (55, 25, 15)],
-                vec![(50, 22, 14), (51, 23, 14)], vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)],
-                vec![(50, 22, 14), (51, 23, 14)], vec![(53, 23, 15), (54, 24, 15)], vec![(54, 24, 15), (55, 25, 15)], vec![(53, 23, 15), (54, 24, 15)], vec![(54, 24, 15), (55, 25, 15)],
-                vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)],
-                vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)], vec![(54, 24, 15), (55, 25, 15)]
-            ],
-            ecc3: [
-                vec![(26, 9, 8)], vec![(44, 16, 14)], vec![(35, 13, 11)], vec![(25, 9, 8)], vec![(33, 11, 11), (34, 12, 11)],
-                vec![(43, 15, 14)], vec![(39, 13, 13), (40, 14, 13)], vec![(40, 14, 13), (41, 15, 13)], vec![(36, 12, 12), (37, 13, 12)], vec![(43, 15, 14), (44, 16, 14)],
-                vec![(36, 12, 12), (37, 13, 12)], vec![(42, 14, 14), (43, 15, 14)], vec![(33, 11, 11), (34, 12, 11)], vec![(36, 12, 12), (37, 13, 12)], vec![(36, 12, 12), (37, 13, 12)],
-                vec![(45, 15, 15), (46, 16, 15)], vec![(42, 14, 14), (43, 15, 14)], vec![(42, 14, 14), (43, 15, 14)], vec![(39, 13, 13), (40, 14, 13)], vec![(43, 15, 14), (44, 16, 14)],
-                vec![(46, 16, 15), (47, 17, 15)], vec![(37, 13, 12)], vec![(45, 15, 15), (46, 16, 15)], vec![(46, 16, 15), (47, 17, 15)], vec![(45, 15, 15), (46, 16, 15)],
-                vec![(46, 16, 15), (47, 17, 15)], vec![(45, 15, 15), (46, 16, 15)], vec![(45, 15, 15), (46, 16, 15)], vec![(45, 15, 15), (46, 16, 15)], vec![(45, 15, 15), (46, 16, 15)],
-                vec![(45, 15, 15), (46, 16, 15)], vec![(45, 15, 15), (46, 16, 15)], vec![(45, 15, 15), (46, 16, 15)], vec![(46, 16, 15), (47, 17, 15)], vec![(45, 15, 15), (46, 16, 15)],
-                vec![(45, 15, 15), (46, 16, 15)], vec![(45, 15, 15), (46, 16, 15)], vec![(45, 15, 15), (46, 16, 15)], vec![(45, 15, 15), (46, 16, 15)], vec![(45, 15, 15), (46, 16, 15)]
-            ]
-        }
-    }
-
-    pub fn consume(self, version: u8, ecc: &ECCLevel) -> (Vec<(usize, usize, usize)>, Vec<usize>) {
-        let version = (version - 1) as usize;
-        (
-            match ecc {
-                ECCLevel::Low => self.ecc0[version].clone(),
-                ECCLevel::Medium => self.ecc1[version].clone(),
-                ECCLevel::Quartile => self.ecc2[version].clone(),
-                ECCLevel::High => self.ecc3[version].clone()
-            },
-            match ecc {
-                ECCLevel::Low => self.n0[version].clone(),
-                ECCLevel::Medium => self.n1[version].clone(),
-                ECCLevel::Quartile => self.n2[version].clone(),
-                ECCLevel::High => self.n3[version].clone()
-            }
-        )
-    }
-}
+//(total codewords, data codewords, error correction capacity) of each block group
+//the third variable is unused for now as it signifies the 'error correction capacity', which is purely informational
+const BLOCK_SIZE: [[[(usize, usize, usize); 2]; 40]; 4] = [
+    [
+        [(26, 19, 2), (0, 0, 0)], [(44, 34, 4), (0, 0, 0)], [(70, 55, 7), (0, 0, 0)], [(100, 80, 10), (0, 0, 0)], [(134, 108, 13), (0, 0, 0)],
+        [(86, 68, 9), (0, 0, 0)], [(98, 78, 10), (0, 0, 0)], [(121, 97, 12), (0, 0, 0)], [(146, 116, 15), (0, 0, 0)], [(86, 68, 9), (87, 69, 9)],
+        [(101, 81, 10), (0, 0, 0)], [(116, 92, 12), (117, 93, 12)], [(133, 107, 13), (0, 0, 0)], [(145, 115, 15), (146, 116, 15)], [(109, 87, 11), (110, 88, 11)],
+        [(122, 98, 12), (123, 99, 12)], [(135, 107, 14), (136, 108, 14)], [(150, 120, 15), (151, 121, 15)], [(141, 113, 14), (142, 114, 14)], [(135, 107, 14), (136, 108, 14)],
+        [(144, 116, 14), (145, 117, 14)], [(139, 111, 14), (140, 112, 14)], [(151, 121, 15), (152, 122, 15)], [(147, 117, 15), (148, 118, 15)], [(132, 106, 13), (133, 107, 13)],
+        [(142, 114, 14), (143, 115, 14)], [(152, 122, 15), (153, 123, 15)], [(147, 117, 15), (148, 118, 15)], [(146, 116, 15), (147, 117, 15)], [(145, 115, 15), (146, 116, 15)],
+        [(145, 115, 15), (146, 116, 15)], [(145, 115, 15), (0, 0, 0)], [(145, 115, 15), (146, 116, 15)], [(145, 115, 15), (146, 116, 15)], [(151, 121, 15), (152, 122, 15)],
+        [(151, 121, 15), (152, 122, 15)], [(152, 122, 15), (153, 123, 15)], [(152, 122, 15), (153, 123, 15)], [(147, 117, 15), (148, 118, 15)], [(148, 118, 15), (149, 119, 15)]
+    ],
+    [
+        [(26, 16, 4), (0, 0, 0)], [(44, 28, 8), (0, 0, 0)], [(70, 44, 13), (0, 0, 0)], [(50, 32, 9), (0, 0, 0)], [(67, 43, 12), (0, 0, 0)],
+        [(43, 27, 8), (0, 0, 0)], [(49, 31, 9), (0, 0, 0)], [(60, 38, 11), (61, 39, 11)], [(58, 36, 11), (59, 37, 11)], [(69, 43, 13), (70, 44, 13)],
+        [(80, 50, 15), (81, 51, 15)], [(58, 36, 11), (59, 37, 11)], [(59, 37, 11), (60, 38, 11)], [(64, 40, 12), (65, 41, 12)], [(65, 41, 12), (66, 42, 12)],
+        [(73, 45, 14), (74, 46, 14)], [(74, 46, 14), (75, 47, 14)], [(69, 43, 13), (70, 44, 13)], [(70, 44, 13), (71, 45, 13)], [(67, 41, 13), (68, 42, 13)],
+        [(68, 42, 13), (0, 0, 0)], [(74, 46, 14), (0, 0, 0)], [(75, 47, 14), (76, 48, 14)], [(73, 45, 14), (74, 46, 14)], [(75, 47, 14), (76, 48, 14)],
+        [(74, 46, 14), (75, 47, 14)], [(73, 45, 14), (74, 46, 14)], [(73, 45, 14), (74, 46, 14)], [(73, 45, 14), (74, 46, 14)], [(75, 47, 14), (76, 48, 14)],
+        [(74, 46, 14), (75, 47, 14)], [(74, 46, 14), (75, 47, 14)], [(74, 46, 14), (75, 47, 14)], [(74, 46, 14), (75, 47, 14)], [(75, 47, 14), (76, 48, 14)],
+        [(75, 47, 14), (76, 48, 14)], [(74, 46, 14), (75, 47, 14)], [(74, 46, 14), (75, 47, 14)], [(75, 47, 14), (76, 48, 14)], [(75, 47, 14), (76, 48, 14)]
+    ],
+    [
+        [(26, 13, 6), (0, 0, 0)], [(44, 22, 11), (0, 0, 0)], [(35, 17, 9), (0, 0, 0)], [(50, 24, 13), (0, 0, 0)], [(33, 15, 9), (34, 16, 9)],
+        [(43, 19, 12), (0, 0, 0)], [(32, 14, 9), (33, 15, 9)], [(40, 18, 11), (41, 19, 11)], [(36, 16, 10), (37, 17, 10)], [(43, 19, 12), (44, 20, 12)],
+        [(50, 22, 14), (51, 23, 14)], [(46, 20, 13), (47, 21, 13)], [(44, 20, 12), (45, 21, 12)], [(36, 16, 10), (37, 17, 10)], [(54, 24, 15), (55, 25, 15)],
+        [(43, 19, 12), (44, 20, 12)], [(50, 22, 14), (51, 23, 14)], [(50, 22, 14), (51, 23, 14)], [(47, 21, 13), (48, 22, 13)], [(54, 24, 15), (55, 25, 15)],
+        [(50, 22, 14), (51, 23, 14)], [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)],
+        [(50, 22, 14), (51, 23, 14)], [(53, 23, 15), (54, 24, 15)], [(54, 24, 15), (55, 25, 15)], [(53, 23, 15), (54, 24, 15)], [(54, 24, 15), (55, 25, 15)],
+        [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)],
+        [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)], [(54, 24, 15), (55, 25, 15)]
+    ],
+    [
+        [(26, 9, 8), (0, 0, 0)], [(44, 16, 14), (0, 0, 0)], [(35, 13, 11), (0, 0, 0)], [(25, 9, 8), (0, 0, 0)], [(33, 11, 11), (34, 12, 11)],
+        [(43, 15, 14), (0, 0, 0)], [(39, 13, 13), (40, 14, 13)], [(40, 14, 13), (41, 15, 13)], [(36, 12, 12), (37, 13, 12)], [(43, 15, 14), (44, 16, 14)],
+        [(36, 12, 12), (37, 13, 12)], [(42, 14, 14), (43, 15, 14)], [(33, 11, 11), (34, 12, 11)], [(36, 12, 12), (37, 13, 12)], [(36, 12, 12), (37, 13, 12)],
+        [(45, 15, 15), (46, 16, 15)], [(42, 14, 14), (43, 15, 14)], [(42, 14, 14), (43, 15, 14)], [(39, 13, 13), (40, 14, 13)], [(43, 15, 14), (44, 16, 14)],
+        [(46, 16, 15), (47, 17, 15)], [(37, 13, 12), (0, 0, 0)], [(45, 15, 15), (46, 16, 15)], [(46, 16, 15), (47, 17, 15)], [(45, 15, 15), (46, 16, 15)],
+        [(46, 16, 15), (47, 17, 15)], [(45, 15, 15), (46, 16, 15)], [(45, 15, 15), (46, 16, 15)], [(45, 15, 15), (46, 16, 15)], [(45, 15, 15), (46, 16, 15)],
+        [(45, 15, 15), (46, 16, 15)], [(45, 15, 15), (46, 16, 15)], [(45, 15, 15), (46, 16, 15)], [(46, 16, 15), (47, 17, 15)], [(45, 15, 15), (46, 16, 15)],
+        [(45, 15, 15), (46, 16, 15)], [(45, 15, 15), (46, 16, 15)], [(45, 15, 15), (46, 16, 15)], [(45, 15, 15), (46, 16, 15)], [(45, 15, 15), (46, 16, 15)]
+    ],
+];
 
-pub fn qr_capacity_query(ecc: &ECCLevel, version: u8) -> usize {
+/// the (total, data, capacity) codewords of each block group and how many blocks each group has
+pub const fn block_division(version: u8, ecc: &ECCLevel) -> ([(usize, usize, usize); 2], [usize; 2]) {
     let idx = match ecc {
         ECCLevel::Low => 0,
         ECCLevel::Medium => 1,
@@ -152,81 +98,166 @@ pub fn qr_capacity_query(ecc: &ECCLevel, version: u8) -> usize {
         ECCLevel::High => 3
     };
 
-    return MAX_SIZE_DATA[idx][(version - 1) as usize];
+    let version = (version - 1) as usize;
+    (BLOCK_SIZE[idx][version], BLOCK_COUNT[idx][version])
+}
+
+/// number of data bits of a version, the data codewords of all its blocks
+pub const fn qr_capacity_query(ecc: &ECCLevel, version: u8) -> usize {
+    let (blocks, blocks_num) = block_division(version, ecc);
+    8 * (blocks[0].1 * blocks_num[0] + blocks[1].1 * blocks_num[1])
 }
 
-pub fn qr_version_query(ecc: &ECCLevel, size: usize) -> u8 {
+//(data bits, error correction codewords) of Micro QR versions M1 to M4 at levels L, M and Q,
+//a single block each, M1 and M3 end on a 4 bit data codeword
+#[cfg(feature = "std")]
+const MICRO_BLOCK: [[(usize, usize); 3]; 4] = [
+    [(20, 2), (0, 0), (0, 0)],
+    [(40, 5), (32, 6), (0, 0)],
+    [(84, 6), (68, 8), (0, 0)],
+    [(128, 8), (112, 10), (80, 14)]
+];
+
+/// the data bits and error correction codewords of Micro QR version `version` (1 to 4),
+/// `None` if the version has no such level (M1 only detects errors, which counts as `Low`)
+#[cfg(feature = "std")]
+pub const fn micro_block(ecc: &ECCLevel, version: u8) -> Option<(usize, usize)> {
     let idx = match ecc {
         ECCLevel::Low => 0,
         ECCLevel::Medium => 1,
         ECCLevel::Quartile => 2,
-        ECCLevel::High => 3
+        ECCLevel::High => return None
     };
 
-    let mut first = 0;
-    let mut last = 40;
-    let mut mid;
+    match MICRO_BLOCK[(version - 1) as usize][idx] {
+        (0, _) => None,
+        block => Some(block)
+    }
+}
 
-    while first < last {
-        mid = (first + last) / 2;
+//(height, width) of rMQR versions R7x43 to R17x139, in the order of their version indicator
+#[cfg(feature = "std")]
+const RMQR_SIZE: [(usize, usize); 32] = [
+    (7, 43), (7, 59), (7, 77), (7, 99), (7, 139),
+    (9, 43), (9, 59), (9, 77), (9, 99), (9, 139),
+    (11, 27), (11, 43), (11, 59), (11, 77), (11, 99), (11, 139),
+    (13, 27), (13, 43), (13, 59), (13, 77), (13, 99), (13, 139),
+    (15, 43), (15, 59), (15, 77), (15, 99), (15, 139),
+    (17, 43), (17, 59), (17, 77), (17, 99), (17, 139)
+];
 
-        if MAX_SIZE_DATA[idx][mid] < size {
-            first = mid + 1;
-        } else {
-            last = mid;
-        }
-    }
+//character count indicator bits of the numeric, alphanumeric, byte and kanji modes of each rMQR version
+#[cfg(feature = "std")]
+const RMQR_COUNT_BITS: [[usize; 4]; 32] = [
+    [4, 3, 3, 2], [5, 5, 4, 3], [6, 5, 5, 4], [7, 6, 5, 5], [7, 6, 6, 5],
+    [5, 5, 4, 3], [6, 5, 5, 4], [7, 6, 5, 5], [7, 6, 6, 5], [8, 7, 6, 6],
+    [4, 4, 3, 2], [6, 5, 5, 4], [7, 6, 5, 5], [7, 6, 6, 5], [8, 7, 6, 6], [8, 7, 7, 6],
+    [5, 5, 4, 3], [6, 6, 5, 5], [7, 6, 6, 5], [7, 7, 6, 5], [8, 7, 7, 6], [8, 8, 7, 7],
+    [7, 6, 6, 5], [7, 7, 6, 5], [8, 7, 7, 6], [8, 7, 7, 6], [9, 8, 7, 7],
+    [7, 6, 6, 5], [8, 7, 6, 6], [8, 7, 7, 6], [8, 8, 7, 6], [9, 8, 8, 7]
+];
+
+//(total codewords, blocks, data codewords) of each rMQR version at levels M and H,
+//every block has as many error correction codewords and the blocks with one more data codeword come last
+#[cfg(feature = "std")]
+const RMQR_BLOCK: [[(usize, usize, usize); 2]; 32] = [
+    [(13, 1, 6), (13, 1, 3)], [(21, 1, 12), (21, 1, 7)], [(32, 1, 20), (32, 1, 10)],
+    [(44, 1, 28), (44, 1, 14)], [(68, 1, 44), (68, 2, 24)],
+    [(21, 1, 12), (21, 1, 7)], [(33, 1, 21), (33, 1, 11)], [(49, 1, 31), (49, 2, 17)],
+    [(66, 1, 42), (66, 2, 22)], [(99, 2, 63), (99, 3, 33)],
+    [(15, 1, 7), (15, 1, 5)], [(31, 1, 19), (31, 1, 11)], [(47, 1, 31), (47, 2, 15)],
+    [(67, 1, 43), (67, 2, 23)], [(89, 2, 57), (89, 2, 29)], [(132, 2, 84), (132, 3, 42)],
+    [(21, 1, 12), (21, 1, 7)], [(41, 1, 27), (41, 1, 13)], [(60, 1, 38), (60, 2, 20)],
+    [(85, 2, 53), (85, 2, 29)], [(113, 2, 73), (113, 3, 35)], [(166, 3, 106), (166, 4, 54)],
+    [(51, 1, 33), (51, 2, 15)], [(74, 1, 48), (74, 2, 26)], [(103, 2, 67), (103, 3, 31)],
+    [(136, 2, 88), (136, 4, 48)], [(199, 3, 127), (199, 5, 69)],
+    [(61, 1, 39), (61, 2, 21)], [(88, 2, 56), (88, 2, 28)], [(122, 2, 78), (122, 3, 38)],
+    [(160, 3, 100), (160, 4, 56)], [(232, 4, 152), (232, 7, 78)]
+];
+
+/// (height, width) of rMQR version `version` (0 to 31)
+#[cfg(feature = "std")]
+pub const fn rmqr_size(version: u8) -> (usize, usize) {
+    RMQR_SIZE[version as usize]
+}
 
-    if first >= 40 {
-        return 41;
+/// character count indicator bits of the numeric, alphanumeric, byte and kanji modes of rMQR version `version`
+#[cfg(feature = "std")]
+pub const fn rmqr_count_bits(version: u8) -> [usize; 4] {
+    RMQR_COUNT_BITS[version as usize]
+}
+
+/// the (total codewords, blocks, data codewords) of rMQR version `version`,
+/// rMQR only has levels M and H so anything but `High` reads as M
+#[cfg(feature = "std")]
+pub const fn rmqr_block(ecc: &ECCLevel, version: u8) -> (usize, usize, usize) {
+    let idx = match ecc {
+        ECCLevel::High => 1,
+        _ => 0
+    };
+
+    RMQR_BLOCK[version as usize][idx]
+}
+
+/// the columns of the alignment patterns (and vertical timing patterns) of rMQR symbols `width` modules wide
+#[cfg(feature = "std")]
+pub const fn obtain_rmqr_alignment(width: usize) -> &'static [usize] {
+    match width {
+        43 => &[21],
+        59 => &[19, 39],
+        77 => &[25, 51],
+        99 => &[23, 49, 75],
+        139 => &[27, 55, 83, 111],
+        _ => &[]
     }
-    
-    return (first + 1) as u8;
 }
 
-pub fn obtain_qr_alignment() -> Vec<Vec<u8>> {
-    vec![
-        vec![],
-        vec![6, 18],
-        vec![6, 22],
-        vec![6, 26],
-        vec![6, 30],
-        vec![6, 34],
-        vec![6, 22, 38],
-        vec![6, 24, 42],
-        vec![6, 26, 46],
-        vec![6, 28, 50],
-        vec![6, 30, 54],
-        vec![6, 32, 58],
-        vec![6, 34, 62],
-        vec![6, 26, 46, 66],
-        vec![6, 26, 48, 70],
-        vec![6, 26, 50, 74],
-        vec![6, 30, 54, 78],
-        vec![6, 30, 56, 82],
-        vec![6, 30, 58, 86],
-        vec![6, 34, 62, 90],
-        vec![6, 28, 50, 72, 94],
-        vec![6, 26, 50, 74, 98],
-        vec![6, 30, 54, 78, 102],
-        vec![6, 28, 54, 80, 106],
-        vec![6, 32, 58, 84, 110],
-        vec![6, 30, 58, 86, 114],
-        vec![6, 34, 62, 90, 118],
-        vec![6, 26, 50, 74, 98, 122],
-        vec![6, 30, 54, 78, 102, 126],
-        vec![6, 26, 52, 78, 104, 130],
-        vec![6, 30, 56, 82, 108, 134],
-        vec![6, 34, 60, 86, 112, 138],
-        vec![6, 30, 58, 86, 114, 142],
-        vec![6, 34, 62, 90, 118, 146],
-        vec![6, 30, 54, 78, 102, 126, 150],
-        vec![6, 24, 50, 76, 102, 128, 154],
-        vec![6, 28, 54, 80, 106, 132, 158],
-        vec![6, 32, 58, 84, 110, 136, 162],
-        vec![6, 26, 54, 82, 110, 138, 166],
-        vec![6, 30, 58, 86, 114, 142, 170],
-    ]
+const ALIGNMENT: [&[u8]; 40] = [
+    &[],
+    &[6, 18],
+    &[6, 22],
+    &[6, 26],
+    &[6, 30],
+    &[6, 34],
+    &[6, 22, 38],
+    &[6, 24, 42],
+    &[6, 26, 46],
+    &[6, 28, 50],
+    &[6, 30, 54],
+    &[6, 32, 58],
+    &[6, 34, 62],
+    &[6, 26, 46, 66],
+    &[6, 26, 48, 70],
+    &[6, 26, 50, 74],
+    &[6, 30, 54, 78],
+    &[6, 30, 56, 82],
+    &[6, 30, 58, 86],
+    &[6, 34, 62, 90],
+    &[6, 28, 50, 72, 94],
+    &[6, 26, 50, 74, 98],
+    &[6, 30, 54, 78, 102],
+    &[6, 28, 54, 80, 106],
+    &[6, 32, 58, 84, 110],
+    &[6, 30, 58, 86, 114],
+    &[6, 34, 62, 90, 118],
+    &[6, 26, 50, 74, 98, 122],
+    &[6, 30, 54, 78, 102, 126],
+    &[6, 26, 52, 78, 104, 130],
+    &[6, 30, 56, 82, 108, 134],
+    &[6, 34, 60, 86, 112, 138],
+    &[6, 30, 58, 86, 114, 142],
+    &[6, 34, 62, 90, 118, 146],
+    &[6, 30, 54, 78, 102, 126, 150],
+    &[6, 24, 50, 76, 102, 128, 154],
+    &[6, 28, 54, 80, 106, 132, 158],
+    &[6, 32, 58, 84, 110, 136, 162],
+    &[6, 26, 54, 82, 110, 138, 166],
+    &[6, 30, 58, 86, 114, 142, 170],
+];
+
+/// the alignment pattern centres on either axis
+pub const fn obtain_qr_alignment(version: u8) -> &'static [u8] {
+    ALIGNMENT[(version - 1) as usize]
 }
 
 pub fn get_format_ecc(data_bits: u8) -> u16 {
@@ -18,7 +18,7 @@ impl GaloisField {
             }
             
             exp[e % 255] = x as u8;
-            log[x as usize] = (e % 255) as u8;
+            log[x] = (e % 255) as u8;
         }
 
         Self { exp, log }
@@ -34,34 +34,21 @@ impl GaloisField {
     }
 }
 
-fn poly_mul(a: &[u8], b: &[u8], field: &GaloisField) -> Vec<u8> {
-    let mut result = vec![0u8; a.len() + b.len() - 1];
+//the error correction blocks never have more than 30 codewords
+const MAX_ERR_LEN: usize = 30;
 
-    for i in 0..result.len() {
-        let mut coeff = 0;
-        for aidx in 0..a.len() {
-            let bidx = i as isize - aidx as isize;
-            if bidx >= b.len() as isize || bidx < 0 {
-                continue;
-            }
-
-            coeff ^= field.mul(a[aidx], b[bidx as usize]);
-        }
-        result[i] = coeff;
-    }
-
-    result
-}
-
-fn generate_generator_poly(field: &GaloisField, err_len: usize) -> Vec<u8> {
-    let mut gen = vec![1u8]; //starts with g(x) = 1
+//g(x) = (x - a^0)(x - a^1)...(x - a^(err_len - 1)), highest degree first
+fn generate_generator_poly(field: &GaloisField, err_len: usize) -> [u8; MAX_ERR_LEN + 1] {
+    let mut gen = [0u8; MAX_ERR_LEN + 1];
+    gen[0] = 1; //starts with g(x) = 1
 
     for i in 0..err_len {
         let root = field.exp[i]; //a^i
-        let next = vec![1, root]; //(x - a^i)
 
-        //multiply current gen(x) by (x - α^i)
-        gen = poly_mul(&gen, &next, field);
+        //multiply current gen(x) by (x - a^i), in place from the lowest degree
+        for j in (1..=i + 1).rev() {
+            gen[j] ^= field.mul(gen[j - 1], root);
+        }
     }
 
     gen
@@ -73,28 +60,29 @@ pub struct ErrorCorrection {
 
 impl ErrorCorrection {
     pub fn new() -> Self {
-        let err = Self {
+        Self {
             gf: GaloisField::new(),
-        };
-        
-        return err;
+        }
     }
 
-    pub fn calculate(&self, bytes: &Vec<u8>, err_len: usize) -> Vec<u8> {
+    /// writes the `err.len()` error correction codewords of `bytes` into `err`
+    pub fn calculate(&self, bytes: &[u8], err: &mut [u8]) {
+        let err_len = err.len();
         let gen = generate_generator_poly(&self.gf, err_len);
-        let mut data = bytes.clone();
-        data.resize(bytes.len() + err_len, 0);
+        err.fill(0);
+
+        //long division, err holds the running remainder
+        for &byte in bytes {
+            let coef = byte ^ err[0];
+            err.copy_within(1.., 0);
+            err[err_len - 1] = 0;
 
-        for i in 0..bytes.len() {
-            let coef = data[i];
             if coef != 0 {
-                for j in 0..gen.len() {
-                    data[i + j] ^= self.gf.mul(coef, gen[j]);
+                for j in 0..err_len {
+                    err[j] ^= self.gf.mul(coef, gen[j + 1]);
                 }
             }
         }
-
-        data[bytes.len()..].to_vec()
     }
 }
 
@@ -110,5 +98,5 @@ pub fn version_information_process(version: u8) -> u32 {
     }
 
     let bch = num & 0xFFF;
-    return ((version as u32) << 12) | bch;
+    ((version as u32) << 12) | bch
 }
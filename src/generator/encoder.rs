@@ -1,84 +1,144 @@
-use std::process::exit;
-
+#[cfg(feature = "hanzi")]
+use encoding_rs::GBK;
+#[cfg(feature = "kanji")]
 use encoding_rs::SHIFT_JIS;
 
-use super::bitstream::BitStream;
+#[cfg(feature = "std")]
+use super::gs1::Fnc1;
+use super::{bitstream::BitStream, Mode};
+use crate::error::QrError;
 
 //[mode indicator] [char count indicator] [encoding bytes] [preferred but optional: 4 times 0s terminator]
+//the encoders write the characters, `encode_header` what comes before them
 pub trait Encoder {
-    //returns free bits in the current byte
-    fn encode(text: &mut std::str::Chars, length: usize, bytes: &mut BitStream, version: u8);
+    fn encode(
+        text: &mut core::str::Chars,
+        length: usize,
+        bytes: &mut BitStream,
+    ) -> Result<(), QrError>;
+}
+
+/// writes the mode indicator and the character count indicator of a segment of `count` characters,
+/// hanzi mode putting its subset indicator (GB2312) between them
+pub fn encode_header(mode: Mode, count: usize, bytes: &mut BitStream, version: u8) {
+    let indicator = match mode {
+        Mode::Numeric => 0b0001,
+        Mode::Alphanumeric => 0b0010,
+        Mode::Byte => 0b0100,
+        Mode::Kanji => 0b1000,
+        Mode::Hanzi => 0b1101,
+    };
+    bytes.push_bits(indicator, 4);
+
+    if mode == Mode::Hanzi {
+        bytes.push_bits(0b0001, 4);
+    }
+
+    bytes.push_bits_big(count, mode.count_bits(version) as u8);
 }
 
 pub struct NumeralEncoder;
 pub struct AlphanumEncoder;
 pub struct BytesEncoder;
+#[cfg(feature = "std")]
 pub struct KanjiEncoder;
+#[cfg(feature = "std")]
+pub struct HanziEncoder;
 
 impl Encoder for NumeralEncoder {
-    fn encode(text: &mut std::str::Chars, length: usize, bytes: &mut BitStream, version: u8) {
-        bytes.push_bits(0b0001, 4);
-
-        if version <= 9 {
-            bytes.push_bits_big(length, 10);
-        } else if version <= 26 {
-            bytes.push_bits_big(length, 12);
-        } else {
-            bytes.push_bits_big(length, 14);
-        }
-
+    fn encode(
+        text: &mut core::str::Chars,
+        length: usize,
+        bytes: &mut BitStream,
+    ) -> Result<(), QrError> {
         let mut start_idx = 0;
         while start_idx < length {
             let s = start_idx;
             let e = length.min(start_idx + 3);
-            let mut text_parse = String::new();
+            let mut x = 0;
             for _ in s..e {
-                text_parse.push(text.next().unwrap_or('\0'));
+                let ch = text.next().unwrap_or('\0');
+                x = 10 * x + ch.to_digit(10).ok_or(QrError::InvalidNumeric(ch))? as usize;
             }
-            match text_parse.parse::<usize>() {
-                Ok(x) => {
-                    match e - s {
-                        1 => bytes.push_bits(x as u8, 4),
-                        2 => bytes.push_bits(x as u8, 7),
-                        3 => bytes.push_bits_big(x, 10),
-                        _ => {}
-                    }
-                },
-                Err(e) => {
-                    eprintln!("Error while parsing text numeral. {e}");
-                    exit(0);
-                }
+
+            match e - s {
+                1 => bytes.push_bits(x as u8, 4),
+                2 => bytes.push_bits(x as u8, 7),
+                3 => bytes.push_bits_big(x, 10),
+                _ => {}
             }
 
             start_idx += 3;
         }
+
+        Ok(())
     }
 }
 
+//None if the text has characters Shift JIS cannot represent
+#[cfg(feature = "kanji")]
+pub(crate) fn encode_shift_jis(text: &str) -> Option<Vec<u8>> {
+    let (encoded, _, err) = SHIFT_JIS.encode(text);
+    if err {
+        None
+    } else {
+        Some(encoded.into_owned())
+    }
+}
+
+//without the kanji feature there is no Shift JIS table, so nothing is kanji
+#[cfg(not(feature = "kanji"))]
+pub fn is_kanji(_ch: char) -> bool {
+    false
+}
+
+#[cfg(feature = "kanji")]
 pub fn is_kanji(ch: char) -> bool {
     let mut buffer = [0; 4];
     let char_str = ch.encode_utf8(&mut buffer);
 
     // Encode the char as SHIFT_JIS.  If encoding succeeds without error, it's encodable.
-    let (encoded, _, err) = SHIFT_JIS.encode(&char_str);
+    let encoded = encode_shift_jis(char_str).unwrap_or_default();
 
-    if !err && encoded.len() > 1 {
+    if encoded.len() > 1 {
         let mut encoded_iter = encoded.iter();
         let b1 = *encoded_iter.next().unwrap();
         let b2 = *encoded_iter.next().unwrap();
         let val = ((b1 as usize) << 8) | b2 as usize;
-        if val >= 0x8140 && val <= 0x9FFC {
-            return true;
-        } else if val >= 0xE040 && val <= 0xEBBF {
-            return true;
-        } else {
-            return false;
-        }
+        (0x8140..=0x9FFC).contains(&val) || (0xE040..=0xEBBF).contains(&val)
     } else {
         false
     }
 }
 
+//None if the text has characters outside of GB2312 subset 1 (the symbols of rows A1-AA and the hanzi of rows B0-FA)
+#[cfg(feature = "hanzi")]
+pub(crate) fn encode_gb2312(text: &str) -> Option<Vec<u8>> {
+    let (encoded, _, err) = GBK.encode(text);
+    let subset = encoded.len() == 2 * text.chars().count()
+        && encoded.chunks(2).all(|pair| {
+            matches!(pair[0], 0xA1..=0xAA | 0xB0..=0xFA) && (0xA1..=0xFE).contains(&pair[1])
+        });
+
+    if err || !subset {
+        None
+    } else {
+        Some(encoded.into_owned())
+    }
+}
+
+//without the hanzi feature there is no GB2312 table, so nothing is hanzi
+#[cfg(not(feature = "hanzi"))]
+pub fn is_hanzi(_ch: char) -> bool {
+    false
+}
+
+#[cfg(feature = "hanzi")]
+pub fn is_hanzi(ch: char) -> bool {
+    let mut buffer = [0; 4];
+    encode_gb2312(ch.encode_utf8(&mut buffer)).is_some()
+}
+
 pub const fn alphanum_value(c: char) -> Option<u8> {
     match c {
         '0' => Some(0),
@@ -131,93 +191,125 @@ pub const fn alphanum_value(c: char) -> Option<u8> {
 }
 
 impl Encoder for AlphanumEncoder {
-    fn encode(text: &mut std::str::Chars, length: usize, bytes: &mut BitStream, version: u8) {
-        bytes.push_bits(0b0010, 4);
-
-        if version <= 9 {
-            bytes.push_bits_big(length, 9);
-        } else if version <= 26 {
-            bytes.push_bits_big(length, 11);
-        } else {
-            bytes.push_bits_big(length, 13);
-        }
-
+    fn encode(
+        text: &mut core::str::Chars,
+        length: usize,
+        bytes: &mut BitStream,
+    ) -> Result<(), QrError> {
         let codes = length / 2;
         for _ in 0..codes {
-            let c1 = alphanum_value(text.next().unwrap_or('\0') as char).unwrap_or(0) as usize;
-            let c2 = alphanum_value(text.next().unwrap_or('\0') as char).unwrap_or(0) as usize;
+            let c1 = alphanum_value(text.next().unwrap_or('\0')).unwrap_or(0) as usize;
+            let c2 = alphanum_value(text.next().unwrap_or('\0')).unwrap_or(0) as usize;
 
             bytes.push_bits_big(c1 * 45 + c2, 11);
         }
 
         if length % 2 == 1 {
             bytes.push_bits_big(
-                alphanum_value(text.next().unwrap_or('\0') as char).unwrap_or(0) as usize,
+                alphanum_value(text.next().unwrap_or('\0')).unwrap_or(0) as usize,
                 6,
             );
         }
+
+        Ok(())
     }
 }
 
 impl Encoder for BytesEncoder {
-    fn encode(text: &mut std::str::Chars, length: usize, bytes: &mut BitStream, version: u8) {
-        bytes.push_bits(0b0100, 4);
+    fn encode(
+        text: &mut core::str::Chars,
+        length: usize,
+        bytes: &mut BitStream,
+    ) -> Result<(), QrError> {
+        let mut buffer = [0; 4];
+        for ch in text.take(length) {
+            for byte in ch.encode_utf8(&mut buffer).as_bytes() {
+                bytes.push(*byte);
+            }
+        }
 
-        //TODO: divide byte into the eci headers
+        Ok(())
+    }
+}
 
-        let mut str = String::new();
-        for _ in 0..length {
-            str.push(text.next().unwrap());
-        }
-        let len = str.len();
-        let mut str_iter = str.chars();
-
-        if version <= 9 {
-            bytes.push(len as u8);
-        } else {
-            bytes.push((len >> 8) as u8);
-            bytes.push((len & 0xFF) as u8);
+/// writes an ECI header, switching the character set of the byte segments that follow
+#[cfg(feature = "std")]
+pub fn encode_eci(designator: u32, bytes: &mut BitStream) {
+    bytes.push_bits(0b0111, 4);
+
+    //the designator takes 1 to 3 bytes, the leading bits tell how many
+    if designator < 1 << 7 {
+        bytes.push(designator as u8);
+    } else if designator < 1 << 14 {
+        bytes.push_bits_big(0b10 << 14 | designator as usize, 16);
+    } else {
+        bytes.push_bits_big(0b110 << 21 | designator as usize, 24);
+    }
+}
+
+/// writes an FNC1 header, in second position followed by the application indicator
+#[cfg(feature = "std")]
+pub fn encode_fnc1(fnc1: Fnc1, bytes: &mut BitStream) {
+    match fnc1 {
+        Fnc1::First => bytes.push_bits(0b0101, 4),
+        Fnc1::Second(indicator) => {
+            bytes.push_bits(0b1001, 4);
+            bytes.push(indicator);
         }
+    }
+}
 
-        for _ in 0..length {
-            let ch = str_iter.next().unwrap_or('\0');
-            let mut text_bytes = [0u8; 4];
-            ch.encode_utf8(&mut text_bytes);
-            
-            for idx in 0..ch.len_utf8() {
-                bytes.push(text_bytes[idx]);
-            }
+/// size of a structured append header, mode indicator included
+#[cfg(feature = "std")]
+pub const STRUCTURED_APPEND_BITS: usize = 20;
+
+/// writes a structured append header: the position of the symbol, the number of symbols
+/// and the parity of the whole data
+#[cfg(feature = "std")]
+pub fn encode_structured_append(index: u8, total: u8, parity: u8, bytes: &mut BitStream) {
+    bytes.push_bits(0b0011, 4);
+    bytes.push_bits(index, 4);
+    bytes.push_bits(total - 1, 4);
+    bytes.push(parity);
+}
+
+impl BytesEncoder {
+    /// writes the octets as they are, used for binary payloads and text in charsets other than UTF-8
+    pub fn encode_raw(data: &[u8], bytes: &mut BitStream) {
+        for byte in data {
+            bytes.push(*byte);
         }
     }
 }
 
+#[cfg(all(feature = "std", not(feature = "kanji")))]
 impl Encoder for KanjiEncoder {
-    fn encode(text: &mut std::str::Chars, length: usize, bytes: &mut BitStream, version: u8) {
-        bytes.push_bits(0b1000, 4);
-
-        if version <= 9 {
-            bytes.push_bits_big(length, 8);
-        } else if version <= 26 {
-            bytes.push_bits_big(length, 10);
-        } else {
-            bytes.push_bits_big(length, 12);
-        }
+    fn encode(
+        _text: &mut core::str::Chars,
+        _length: usize,
+        _bytes: &mut BitStream,
+    ) -> Result<(), QrError> {
+        Err(QrError::UnencodableKanji)
+    }
+}
 
+#[cfg(feature = "kanji")]
+impl Encoder for KanjiEncoder {
+    fn encode(
+        text: &mut core::str::Chars,
+        length: usize,
+        bytes: &mut BitStream,
+    ) -> Result<(), QrError> {
         let mut str = String::new();
         for _ in 0..length {
             str.push(text.next().unwrap());
         }
-        let (encoded, _, error) = SHIFT_JIS.encode(&str);
-
-        if error {
-            eprintln!("Error: cannot encode kanji.");
-            exit(0);
-        }
+        let encoded = encode_shift_jis(&str).ok_or(QrError::UnencodableKanji)?;
 
         let mut encoded_iter = encoded.iter();
         while let Some(byte_val) = encoded_iter.next() {
             let b1 = *byte_val;
-            let b2 = *encoded_iter.next().unwrap();
+            let b2 = *encoded_iter.next().ok_or(QrError::UnencodableKanji)?;
 
             let mut val = ((b1 as usize) << 8) | b2 as usize;
 
@@ -230,5 +322,47 @@ impl Encoder for KanjiEncoder {
             val = (val & 0xFF) + (val >> 8) * 0xC0;
             bytes.push_bits_big(val, 13);
         }
+
+        Ok(())
+    }
+}
+#[cfg(all(feature = "std", not(feature = "hanzi")))]
+impl Encoder for HanziEncoder {
+    fn encode(
+        text: &mut core::str::Chars,
+        _length: usize,
+        _bytes: &mut BitStream,
+    ) -> Result<(), QrError> {
+        Err(QrError::UnencodableCharacter {
+            ch: text.next().unwrap_or_default(),
+            mode: Mode::Hanzi,
+        })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "hanzi")]
+impl Encoder for HanziEncoder {
+    fn encode(
+        text: &mut core::str::Chars,
+        length: usize,
+        bytes: &mut BitStream,
+    ) -> Result<(), QrError> {
+        let str = text.take(length).collect::<String>();
+        let encoded = encode_gb2312(&str).ok_or_else(|| QrError::UnencodableCharacter {
+            ch: str.chars().find(|&ch| !is_hanzi(ch)).unwrap_or_default(),
+            mode: Mode::Hanzi,
+        })?;
+
+        for pair in encoded.chunks(2) {
+            let row = if pair[0] <= 0xAA {
+                pair[0] - 0xA1
+            } else {
+                pair[0] - 0xA6
+            };
+            let val = row as usize * 0x60 + (pair[1] - 0xA1) as usize;
+            bytes.push_bits_big(val, 13);
+        }
+
+        Ok(())
+    }
+}
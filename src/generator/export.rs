@@ -0,0 +1,463 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use super::input::{decode_hex, encode_hex};
+use super::{
+    matrix::{matrix_len, side, stride, Matrix},
+    ECCLevel, Mode, QRCode, Segment,
+};
+use crate::error::QrError;
+
+//"QRC" followed by the revision of the binary format
+const MAGIC: [u8; 4] = *b"QRC\x01";
+
+fn ecc_char(ecc: ECCLevel) -> char {
+    match ecc {
+        ECCLevel::Low => 'L',
+        ECCLevel::Medium => 'M',
+        ECCLevel::Quartile => 'Q',
+        ECCLevel::High => 'H',
+    }
+}
+
+fn ecc_from_char(ch: char) -> Result<ECCLevel, QrError> {
+    match ch {
+        'L' => Ok(ECCLevel::Low),
+        'M' => Ok(ECCLevel::Medium),
+        'Q' => Ok(ECCLevel::Quartile),
+        'H' => Ok(ECCLevel::High),
+        _ => Err(QrError::InvalidSymbol(format!("unknown error correction level '{}'.", ch))),
+    }
+}
+
+fn mode_index(mode: Mode) -> u8 {
+    match mode {
+        Mode::Numeric => 0,
+        Mode::Alphanumeric => 1,
+        Mode::Byte => 2,
+        Mode::Kanji => 3,
+        Mode::Hanzi => 4,
+    }
+}
+
+#[cfg(feature = "serde")]
+fn mode_name(mode: Mode) -> &'static str {
+    match mode {
+        Mode::Numeric => "numeric",
+        Mode::Alphanumeric => "alphanumeric",
+        Mode::Byte => "byte",
+        Mode::Kanji => "kanji",
+        Mode::Hanzi => "hanzi",
+    }
+}
+
+#[cfg(feature = "serde")]
+fn mode_from_name(name: &str) -> Result<Mode, QrError> {
+    match name {
+        "numeric" => Ok(Mode::Numeric),
+        "alphanumeric" => Ok(Mode::Alphanumeric),
+        "byte" => Ok(Mode::Byte),
+        "kanji" => Ok(Mode::Kanji),
+        "hanzi" => Ok(Mode::Hanzi),
+        _ => Err(QrError::InvalidSymbol(format!("unknown segment mode '{}'.", name))),
+    }
+}
+
+//a text segment, or a binary one if `text` is None
+fn segment(mode: Mode, text: Option<&str>, data: &[u8]) -> Result<Segment, QrError> {
+    match text {
+        Some(text) => Segment::new(mode, text),
+        None if mode == Mode::Byte => Ok(Segment::binary(data)),
+        None => Err(QrError::InvalidSymbol(format!(
+            "only byte segments can hold binary data, not {:?}.",
+            mode
+        ))),
+    }
+}
+
+//checks the header against the rows before building the symbol
+fn symbol(
+    version: u8,
+    ecc: ECCLevel,
+    mask: u8,
+    size: usize,
+    rows: Vec<u8>,
+    segments: Vec<Segment>,
+) -> Result<QRCode, QrError> {
+    if !(1..=40).contains(&version) {
+        return Err(QrError::InvalidSymbol(format!("version {} is not between 1 and 40.", version)));
+    }
+
+    if mask > 7 {
+        return Err(QrError::InvalidSymbol(format!("mask {} is not between 0 and 7.", mask)));
+    }
+
+    if size != side(version) {
+        return Err(QrError::InvalidSymbol(format!(
+            "a version {} symbol is {} modules wide, not {}.",
+            version,
+            side(version),
+            size
+        )));
+    }
+
+    if rows.len() != matrix_len(version) {
+        return Err(QrError::InvalidSymbol(format!(
+            "expected {} bytes of rows but found {}.",
+            matrix_len(version),
+            rows.len()
+        )));
+    }
+
+    Ok(QRCode::from_matrix(Matrix::new(rows, version), ecc, mask).with_segments(segments))
+}
+
+//reads the binary form front to back
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], QrError> {
+        if self.data.len() < len {
+            return Err(QrError::InvalidSymbol(String::from("unexpected end of data.")));
+        }
+
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn byte(&mut self) -> Result<u8, QrError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn short(&mut self) -> Result<usize, QrError> {
+        let bytes = self.take(2)?;
+        Ok(((bytes[0] as usize) << 8) | bytes[1] as usize)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct SymbolJson {
+    version: u8,
+    ecc: char,
+    mask: u8,
+    size: usize,
+    //one hex string per row, 8 modules per byte
+    rows: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    segments: Option<Vec<SegmentJson>>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct SegmentJson {
+    mode: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    //binary segments only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hex: Option<String>,
+}
+
+impl QRCode {
+    /// the compact binary form, all numbers big endian:
+    /// "QRC" and the format revision (1), version, ECC level (L, M, Q or H in ASCII), mask, size,
+    /// the packed rows (see `as_bytes`), then the number of segments (2 bytes, 0 without `segments`)
+    /// each being its mode (0-4, see `Mode::from_index`), 1 if binary else 0, the length of its data (2 bytes) and the data
+    pub fn to_binary(&self, segments: bool) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&[
+            self.version(),
+            ecc_char(self.ecc_level()) as u8,
+            self.mask(),
+            self.size() as u8,
+        ]);
+        out.extend_from_slice(self.as_bytes());
+
+        let segments = if segments { self.segments() } else { &[] };
+        out.extend_from_slice(&(segments.len() as u16).to_be_bytes());
+        for seg in segments {
+            out.push(mode_index(seg.mode()));
+            out.push(seg.text().is_none() as u8);
+            out.extend_from_slice(&(seg.data().len() as u16).to_be_bytes());
+            out.extend_from_slice(seg.data());
+        }
+
+        out
+    }
+
+    /// reads back the output of `to_binary`
+    pub fn from_binary(data: &[u8]) -> Result<QRCode, QrError> {
+        let mut reader = Reader { data };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(QrError::InvalidSymbol(String::from("not a serialized QR code.")));
+        }
+
+        let version = reader.byte()?;
+        let ecc = ecc_from_char(reader.byte()? as char)?;
+        let mask = reader.byte()?;
+        let size = reader.byte()? as usize;
+        let rows = reader.take(size * stride(size))?.to_vec();
+
+        let mut segments = Vec::new();
+        for _ in 0..reader.short()? {
+            let mode = reader.byte()?;
+            let mode = Mode::from_index(mode)
+                .map_err(|_| QrError::InvalidSymbol(format!("unknown segment mode {}.", mode)))?;
+            let binary = reader.byte()? == 1;
+            let len = reader.short()?;
+            let data = reader.take(len)?;
+
+            let text = if binary {
+                None
+            } else {
+                Some(std::str::from_utf8(data).map_err(|_| {
+                    QrError::InvalidSymbol(String::from("segment text is not UTF-8."))
+                })?)
+            };
+            segments.push(segment(mode, text, data)?);
+        }
+
+        if !reader.data.is_empty() {
+            return Err(QrError::InvalidSymbol(String::from("trailing data after the segments.")));
+        }
+
+        symbol(version, ecc, mask, size, rows, segments)
+    }
+
+    /// the symbol as JSON: version, ecc ("L", "M", "Q" or "H"), mask, size, rows (one hex string
+    /// per row, see `as_bytes`) and, with `segments`, the segments as their mode and either their
+    /// text or the hex of their binary data
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self, segments: bool) -> String {
+        let json = SymbolJson {
+            version: self.version(),
+            ecc: ecc_char(self.ecc_level()),
+            mask: self.mask(),
+            size: self.size(),
+            rows: self
+                .as_bytes()
+                .chunks(stride(self.size()))
+                .map(encode_hex)
+                .collect(),
+            segments: segments.then(|| {
+                self.segments()
+                    .iter()
+                    .map(|seg| SegmentJson {
+                        mode: String::from(mode_name(seg.mode())),
+                        text: seg.text().map(String::from),
+                        hex: seg.text().is_none().then(|| encode_hex(seg.data())),
+                    })
+                    .collect()
+            }),
+        };
+
+        //the fields are plain strings and numbers, this cannot fail
+        serde_json::to_string_pretty(&json).unwrap_or_default()
+    }
+
+    /// reads back the output of `to_json`
+    #[cfg(feature = "serde")]
+    pub fn from_json(text: &str) -> Result<QRCode, QrError> {
+        let json: SymbolJson =
+            serde_json::from_str(text).map_err(|e| QrError::InvalidSymbol(e.to_string()))?;
+        let hex = |text: &str| {
+            decode_hex(text).map_err(|e| QrError::InvalidSymbol(e.to_string()))
+        };
+
+        let mut rows = Vec::new();
+        for row in &json.rows {
+            let row = hex(row)?;
+            if row.len() != stride(json.size) {
+                return Err(QrError::InvalidSymbol(format!(
+                    "rows of a symbol {} modules wide are {} bytes, not {}.",
+                    json.size,
+                    stride(json.size),
+                    row.len()
+                )));
+            }
+            rows.extend(row);
+        }
+
+        let mut segments = Vec::new();
+        for seg in json.segments.unwrap_or_default() {
+            let mode = mode_from_name(&seg.mode)?;
+            let data = match &seg.hex {
+                Some(data) => hex(data)?,
+                None => Vec::new(),
+            };
+
+            if seg.text.is_none() && seg.hex.is_none() {
+                return Err(QrError::InvalidSymbol(String::from(
+                    "segments need either text or hex.",
+                )));
+            }
+            segments.push(segment(mode, seg.text.as_deref(), &data)?);
+        }
+
+        symbol(json.version, ecc_from_char(json.ecc)?, json.mask, json.size, rows, segments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{Generator, QrOptions};
+
+    fn encode(text: &str, hanzi: bool) -> QRCode {
+        let mut builder = QrOptions::builder();
+        builder.ecc(ECCLevel::Quartile).hanzi(hanzi);
+        Generator::new(String::from(text), builder.build().unwrap())
+            .run()
+            .unwrap()
+    }
+
+    fn binary() -> QRCode {
+        Generator::from_segments(
+            vec![Segment::numeric("2024").unwrap(), Segment::binary(&[0, 0xFF, 0x80])],
+            QrOptions::builder().build().unwrap(),
+        )
+        .run()
+        .unwrap()
+    }
+
+    fn assert_same(read: &QRCode, symbol: &QRCode, segments: bool) {
+        assert_eq!(read.version(), symbol.version());
+        assert_eq!(read.ecc_level(), symbol.ecc_level());
+        assert_eq!(read.mask(), symbol.mask());
+        assert_eq!(read.size(), symbol.size());
+        assert_eq!(read.as_bytes(), symbol.as_bytes());
+        if segments {
+            assert_eq!(read.segments(), symbol.segments());
+        } else {
+            assert!(read.segments().is_empty());
+        }
+    }
+
+    fn invalid(result: Result<QRCode, QrError>) -> String {
+        match result {
+            Err(QrError::InvalidSymbol(msg)) => msg,
+            Err(e) => panic!("expected InvalidSymbol, got {:?}", e),
+            Ok(_) => panic!("expected InvalidSymbol"),
+        }
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        for symbol in [encode("HELLO 0123456789 world", false), binary()] {
+            for segments in [true, false] {
+                let read = QRCode::from_binary(&symbol.to_binary(segments)).unwrap();
+                assert_same(&read, &symbol, segments);
+            }
+        }
+    }
+
+    #[cfg(feature = "kanji")]
+    #[test]
+    fn kanji_round_trip() {
+        let symbol = encode("漢字のテスト", false);
+        assert!(symbol.segments().iter().any(|seg| seg.mode() == Mode::Kanji));
+        assert_same(&QRCode::from_binary(&symbol.to_binary(true)).unwrap(), &symbol, true);
+        #[cfg(feature = "serde")]
+        assert_same(&QRCode::from_json(&symbol.to_json(true)).unwrap(), &symbol, true);
+    }
+
+    #[cfg(feature = "hanzi")]
+    #[test]
+    fn hanzi_round_trip() {
+        let symbol = encode("汉字测试", true);
+        assert!(symbol.segments().iter().any(|seg| seg.mode() == Mode::Hanzi));
+        let data = symbol.to_binary(true);
+        //the mode of the first segment, right after the segment count
+        assert_eq!(data[8 + symbol.as_bytes().len() + 2], 4);
+        assert_same(&QRCode::from_binary(&data).unwrap(), &symbol, true);
+        #[cfg(feature = "serde")]
+        assert_same(&QRCode::from_json(&symbol.to_json(true)).unwrap(), &symbol, true);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        for symbol in [encode("HELLO 0123456789 world", false), binary()] {
+            for segments in [true, false] {
+                let read = QRCode::from_json(&symbol.to_json(segments)).unwrap();
+                assert_same(&read, &symbol, segments);
+            }
+        }
+
+        assert!(binary().to_json(true).contains("\"hex\": \"00ff80\""));
+    }
+
+    #[test]
+    fn malformed_binary() {
+        let data = binary().to_binary(true);
+
+        let mut magic = data.clone();
+        magic[0] = b'X';
+        assert_eq!(invalid(QRCode::from_binary(&magic)), "not a serialized QR code.");
+
+        //cut inside the rows, then inside the last segment
+        for len in [20, data.len() - 1] {
+            let msg = invalid(QRCode::from_binary(&data[..len]));
+            assert_eq!(msg, "unexpected end of data.");
+        }
+        assert_eq!(invalid(QRCode::from_binary(&[])), "unexpected end of data.");
+
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert_eq!(
+            invalid(QRCode::from_binary(&trailing)),
+            "trailing data after the segments."
+        );
+
+        //a version 2 header in front of the 21 modules wide rows of version 1
+        let mut size = data.clone();
+        size[4] = 2;
+        assert_eq!(
+            invalid(QRCode::from_binary(&size)),
+            "a version 2 symbol is 25 modules wide, not 21."
+        );
+
+        let mut mask = data.clone();
+        mask[6] = 8;
+        assert_eq!(invalid(QRCode::from_binary(&mask)), "mask 8 is not between 0 and 7.");
+
+        let mut ecc = data.clone();
+        ecc[5] = b'X';
+        invalid(QRCode::from_binary(&ecc));
+
+        //the mode of the first segment
+        let mut mode = data;
+        mode[8 + 21 * 3 + 2] = 5;
+        assert_eq!(invalid(QRCode::from_binary(&mode)), "unknown segment mode 5.");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn malformed_json() {
+        let json = binary().to_json(true);
+
+        invalid(QRCode::from_json(&json[..json.len() / 2]));
+        invalid(QRCode::from_json(&json.replace("\"byte\"", "\"octet\"")));
+
+        let size = json.replace("\"size\": 21", "\"size\": 25");
+        assert_eq!(
+            invalid(QRCode::from_json(&size)),
+            "rows of a symbol 25 modules wide are 4 bytes, not 3."
+        );
+
+        let version = json.replace("\"version\": 1", "\"version\": 2");
+        assert_eq!(
+            invalid(QRCode::from_json(&version)),
+            "a version 2 symbol is 25 modules wide, not 21."
+        );
+
+        //binary data in a mode other than byte
+        let numeric = json.replace("\"byte\"", "\"numeric\"");
+        assert!(invalid(QRCode::from_json(&numeric)).starts_with("only byte segments"));
+    }
+}
@@ -0,0 +1,143 @@
+use super::{
+    bitstream::BitStream,
+    codewords::{finish, interleaved, total_codewords},
+    data::qr_capacity_query,
+    encoder::{
+        alphanum_value, encode_header, AlphanumEncoder, BytesEncoder, Encoder, NumeralEncoder,
+    },
+    matrix::{matrix_len, Matrix},
+    ECCLevel, Mode,
+};
+use crate::error::QrError;
+
+/// bytes of codeword buffer `encode_into` needs for any symbol up to `max_version`
+pub const fn codeword_buffer_len(max_version: u8) -> usize {
+    total_codewords(max_version)
+}
+
+/// bytes of matrix buffer `encode_into` needs for any symbol up to `max_version`
+pub const fn matrix_buffer_len(max_version: u8) -> usize {
+    matrix_len(max_version)
+}
+
+/// a symbol encoded by `encode_into`, borrowing the caller's matrix buffer
+#[derive(Debug, Clone)]
+pub struct QrMatrix<'a> {
+    matrix: Matrix<&'a [u8]>,
+    ecc: ECCLevel,
+    mask: u8,
+}
+
+impl QrMatrix<'_> {
+    /// number of modules on each side of the symbol (quiet zone excluded)
+    pub fn size(&self) -> usize {
+        self.matrix.size()
+    }
+
+    pub fn version(&self) -> u8 {
+        self.matrix.version()
+    }
+
+    pub fn ecc_level(&self) -> ECCLevel {
+        self.ecc
+    }
+
+    /// mask pattern (0-7) chosen by the penalty evaluation
+    pub fn mask(&self) -> u8 {
+        self.mask
+    }
+
+    /// true if module (x, y) is dark, (0, 0) being the top left module
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.size() && y < self.size() && self.matrix.get(x, y)
+    }
+
+    /// the rows of the symbol, 8 modules per byte (most significant bit first),
+    /// every row starts on a new byte
+    pub fn as_bytes(&self) -> &[u8] {
+        self.matrix.bytes()
+    }
+}
+
+//the one mode that holds all of `data`
+fn single_mode(data: &[u8]) -> Mode {
+    if data.iter().all(u8::is_ascii_digit) {
+        Mode::Numeric
+    } else if data.iter().all(|&byte| alphanum_value(byte as char).is_some()) {
+        Mode::Alphanumeric
+    } else {
+        Mode::Byte
+    }
+}
+
+/// encodes `data` without allocating, in a single segment of the densest mode that holds all of it
+///
+/// `codewords` is scratch space and `matrix` receives the symbol, size them with
+/// `codeword_buffer_len` and `matrix_buffer_len` for the largest version you accept
+pub fn encode_into<'a>(
+    data: &[u8],
+    ecc: ECCLevel,
+    codewords: &mut [u8],
+    matrix: &'a mut [u8],
+) -> Result<QrMatrix<'a>, QrError> {
+    if data.is_empty() {
+        return Err(QrError::EmptyInput);
+    }
+
+    let mode = single_mode(data);
+    let version = (1..=40)
+        .find(|&version| {
+            mode.header_bits(version) + mode.data_bits(data.len())
+                <= qr_capacity_query(&ecc, version)
+        })
+        .ok_or(QrError::NoVersionFits)?;
+
+    if codewords.len() < codeword_buffer_len(version) {
+        return Err(QrError::BufferTooSmall { needed: codeword_buffer_len(version) });
+    }
+
+    if matrix.len() < matrix_buffer_len(version) {
+        return Err(QrError::BufferTooSmall { needed: matrix_buffer_len(version) });
+    }
+
+    let capacity = qr_capacity_query(&ecc, version);
+    let mut stream = BitStream::new(&mut codewords[..capacity / 8]);
+    encode_header(mode, data.len(), &mut stream, version);
+    match mode {
+        Mode::Byte => BytesEncoder::encode_raw(data, &mut stream),
+        _ => {
+            //numeric and alphanumeric data is ASCII, so always valid UTF-8
+            let text = core::str::from_utf8(data).unwrap_or_default();
+            let mut chars = text.chars();
+            match mode {
+                Mode::Numeric => NumeralEncoder::encode(&mut chars, data.len(), &mut stream)?,
+                _ => AlphanumEncoder::encode(&mut chars, data.len(), &mut stream)?,
+            }
+        }
+    }
+
+    if stream.overflowed() {
+        return Err(QrError::CapacityExceeded {
+            bits: stream.size(),
+            capacity,
+        });
+    }
+
+    if stream.size() <= capacity - 4 {
+        stream.push_bits(0, 4);
+    }
+
+    let used = stream.len();
+    finish(codewords, used, version, &ecc);
+
+    let mut work = Matrix::new(&mut *matrix, version);
+    work.generate(interleaved(codewords, version, &ecc));
+    let (mask, _) = work.mask(&ecc, None);
+
+    let matrix: &'a [u8] = matrix;
+    Ok(QrMatrix {
+        matrix: Matrix::new(matrix, version),
+        ecc,
+        mask,
+    })
+}
@@ -0,0 +1,794 @@
+use std::fmt;
+
+use super::{
+    encoder::alphanum_value,
+    url::{authority_len, smallest},
+    QrOptions,
+};
+use crate::error::QrError;
+
+/// the ASCII group separator ending a variable length field, written as `%` in alphanumeric mode
+pub const GS: char = '\u{1D}';
+
+/// the FNC1 mode of a symbol, announced by a header before the first segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fnc1 {
+    /// the data is GS1 element strings
+    First,
+    /// the data follows an industry specification identified by its application indicator,
+    /// two digits as their value (0-99) or a letter as its ASCII value + 100
+    Second(u8),
+}
+
+impl Fnc1 {
+    /// size of the header, mode indicator included
+    pub(crate) fn bits(self) -> usize {
+        match self {
+            Fnc1::First => 4,
+            Fnc1::Second(_) => 12,
+        }
+    }
+}
+
+//what the data field of an application identifier holds
+struct Format {
+    numeric: bool,
+    min: usize,
+    max: usize,
+    //the field starts with this many digits, a GS1 key for the ones with a check digit
+    key: usize,
+    //the last digit of the key is a GS1 check digit
+    check: bool,
+    //YYMMDD
+    date: bool,
+}
+
+impl Format {
+    const fn digits(len: usize) -> Self {
+        Self::between_digits(len, len)
+    }
+
+    const fn between_digits(min: usize, max: usize) -> Self {
+        Self {
+            numeric: true,
+            min,
+            max,
+            key: 0,
+            check: false,
+            date: false,
+        }
+    }
+
+    const fn up_to_digits(max: usize) -> Self {
+        Self::between_digits(1, max)
+    }
+
+    const fn up_to(max: usize) -> Self {
+        Self {
+            numeric: false,
+            ..Self::up_to_digits(max)
+        }
+    }
+
+    //`key` digits followed by up to `max - key` characters
+    const fn after_digits(key: usize, max: usize) -> Self {
+        Self {
+            min: key,
+            key,
+            ..Self::up_to(max)
+        }
+    }
+
+    //a GS1 key of `key` digits followed by up to `max - key` characters (digits if `numeric`)
+    const fn keyed(key: usize, max: usize, numeric: bool) -> Self {
+        Self {
+            numeric,
+            check: true,
+            ..Self::after_digits(key, max)
+        }
+    }
+
+    const fn checked(len: usize) -> Self {
+        Self::keyed(len, len, true)
+    }
+
+    const fn date() -> Self {
+        Self {
+            date: true,
+            ..Self::digits(6)
+        }
+    }
+
+    //the format of `ai` in the GS1 General Specifications, `None` if it is not listed
+    fn of(ai: &str) -> Option<Self> {
+        let digit = |idx: usize| {
+            let byte = ai
+                .as_bytes()
+                .get(idx)
+                .filter(|byte| byte.is_ascii_digit())?;
+            Some(byte - b'0')
+        };
+        Some(match ai {
+            "00" => Self::checked(18),
+            "01" | "02" | "03" => Self::checked(14),
+            "10" | "21" | "22" | "243" | "254" | "420" | "4318" | "7020" | "7021" | "7022"
+            | "7240" | "8002" | "8012" => Self::up_to(20),
+            "11" | "12" | "13" | "15" | "16" | "17" | "4326" | "7006" => Self::date(),
+            "20" | "7241" => Self::digits(2),
+            "235" => Self::up_to(28),
+            "242" => Self::up_to_digits(6),
+            "253" => Self::keyed(13, 30, false),
+            "255" => Self::keyed(13, 25, true),
+            "30" | "37" => Self::up_to_digits(8),
+            "240" | "241" | "250" | "251" | "400" | "401" | "403" | "4308" | "4319" | "7002"
+            | "7023" | "8004" | "8010" | "90" => Self::up_to(30),
+            "402" => Self::checked(17),
+            "421" => Self::after_digits(3, 12),
+            "422" | "424" | "426" => Self::digits(3),
+            "423" | "425" => Self::between_digits(4, 15),
+            "427" | "7008" => Self::up_to(3),
+            "4300" | "4301" | "4310" | "4311" | "4320" => Self::up_to(35),
+            "4302" | "4303" | "4304" | "4305" | "4306" | "4312" | "4313" | "4314" | "4315"
+            | "4316" | "7257" | "8110" | "8112" | "8200" => Self::up_to(70),
+            "4307" | "4317" | "7010" => Self::up_to(2),
+            "4309" => Self::digits(20),
+            "4321" | "4322" | "4323" | "7252" => Self::digits(1),
+            "4324" | "4325" => Self::digits(10),
+            //temperatures, 6 digits and an optional '-'
+            "4330" | "4331" | "4332" | "4333" => Self::after_digits(6, 7),
+            "7001" => Self::digits(13),
+            "7003" => Self::digits(10),
+            "7004" => Self::up_to_digits(4),
+            "7005" => Self::up_to(12),
+            //a date or a range of two
+            "7007" => Self::between_digits(6, 12),
+            "7009" | "7255" => Self::up_to(10),
+            "7011" => Self::between_digits(6, 10),
+            "7040" => Self::after_digits(1, 4),
+            "710" | "711" | "712" | "713" | "714" | "715" | "716" => Self::up_to(20),
+            "7242" | "8013" | "8020" => Self::up_to(25),
+            //YYYYMMDD and YYYYMMDDHHMM
+            "7250" => Self::digits(8),
+            "7251" => Self::digits(12),
+            "7253" | "7254" | "7259" => Self::up_to(40),
+            "7256" | "8030" => Self::up_to(90),
+            "7258" => Self::up_to(3),
+            "8001" => Self::digits(14),
+            "8003" => Self::keyed(14, 30, false),
+            "8005" => Self::digits(6),
+            "8006" | "8026" => Self::digits(18),
+            "8007" => Self::up_to(34),
+            "8008" => Self::between_digits(8, 12),
+            "8009" => Self::up_to(50),
+            "8011" => Self::up_to_digits(12),
+            "8017" | "8018" => Self::checked(18),
+            "8019" => Self::up_to_digits(10),
+            "8111" => Self::digits(4),
+            _ if ai.len() == 4 && ai.starts_with("703") => Self::after_digits(3, 30),
+            _ if ai.len() == 4 && ai.starts_with("723") => Self::up_to(30),
+            //the 4th digit of these is where the decimal point goes
+            _ if ai.len() == 4 => match (digit(0)?, digit(1)?, digit(2)?) {
+                (3, 1, 0..=6)
+                | (3, 2, _)
+                | (3, 3, 0..=7)
+                | (3, 4, _)
+                | (3, 5, 0..=7)
+                | (3, 6, _)
+                | (3, 9, 5) => Self::digits(6),
+                (3, 9, 0 | 2) => Self::up_to_digits(15),
+                //ISO 4217 currency code and amount
+                (3, 9, 1 | 3) => Self::between_digits(4, 18),
+                (3, 9, 4) => Self::digits(4),
+                _ => return None,
+            },
+            _ if ai.len() == 3 && ai.starts_with("41") && digit(2)? <= 7 => Self::checked(13),
+            _ if ai.len() == 2 && ai.starts_with('9') => Self::up_to(90),
+            _ => return None,
+        })
+    }
+
+    //the format of an AI that is not listed: digits of the predefined length of its prefix,
+    //otherwise anything up to the longest data field
+    fn unlisted(ai: &str) -> Self {
+        match predefined_len(ai) {
+            Some(len) => Self::digits(len - ai.len()),
+            None => Self::up_to(90),
+        }
+    }
+}
+
+/// alphanumeric text in FNC1 mode, where `%` stands for `GS` and a `%` is doubled
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('%', "%%").replace(GS, "%")
+}
+
+/// the GS1 check digit of `digits` (the digits before it)
+pub(crate) fn check_digit(digits: &[u8]) -> u8 {
+    let sum = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(idx, digit)| (digit - b'0') as usize * if idx % 2 == 0 { 3 } else { 1 })
+        .sum::<usize>();
+    ((10 - sum % 10) % 10) as u8
+}
+
+/// true if `ch` belongs to GS1 AI encodable character set 82
+fn is_cset82(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || "!\"%&'()*+,-./:;<=>?_".contains(ch)
+}
+
+/// the length of the element strings (AI included) starting with the prefix of `ai`,
+/// `None` if their data field is not of predefined length
+///
+/// a few AIs of these prefixes have a field of variable length all the same (235), see `needs_separator`
+fn predefined_len(ai: &str) -> Option<usize> {
+    const PREFIXES: [(&str, usize); 23] = [
+        ("00", 20),
+        ("01", 16),
+        ("02", 16),
+        ("03", 16),
+        ("04", 18),
+        ("11", 8),
+        ("12", 8),
+        ("13", 8),
+        ("14", 8),
+        ("15", 8),
+        ("16", 8),
+        ("17", 8),
+        ("18", 8),
+        ("19", 8),
+        ("20", 4),
+        ("23", 19),
+        ("31", 10),
+        ("32", 10),
+        ("33", 10),
+        ("34", 10),
+        ("35", 10),
+        ("36", 10),
+        ("41", 16),
+    ];
+    PREFIXES
+        .iter()
+        .find(|(prefix, _)| ai.starts_with(prefix))
+        .map(|&(_, len)| len)
+}
+
+/// true if a `GS` has to end the data field of `ai` when another element string follows it,
+/// which is all but the fields of fixed length whose prefix is of predefined length
+fn needs_separator(ai: &str) -> bool {
+    let format = Format::of(ai).unwrap_or_else(|| Format::unlisted(ai));
+    predefined_len(ai).is_none() || format.min != format.max
+}
+
+/// fails if `value` does not fit the data field of `ai`
+pub(crate) fn validate(ai: &str, value: &str) -> Result<(), QrError> {
+    let invalid = |msg: String| Err(QrError::InvalidGs1(format!("({}){}: {}", ai, value, msg)));
+    let format = Format::of(ai).unwrap_or_else(|| Format::unlisted(ai));
+
+    let len = value.chars().count();
+    if len < format.min || len > format.max {
+        return invalid(if format.min == format.max {
+            format!("must be {} characters long.", format.max)
+        } else {
+            format!("must be {} to {} characters long.", format.min, format.max)
+        });
+    }
+
+    if let Some(ch) = value.chars().enumerate().find_map(|(idx, ch)| {
+        let allowed = if format.numeric || idx < format.key {
+            ch.is_ascii_digit()
+        } else {
+            is_cset82(ch)
+        };
+        (!allowed).then_some(ch)
+    }) {
+        return invalid(format!("'{}' is not allowed.", ch));
+    }
+
+    let digits = value.as_bytes();
+    if format.check {
+        let check = check_digit(&digits[..format.key - 1]);
+        if digits[format.key - 1] - b'0' != check {
+            return invalid(format!("the check digit should be {}.", check));
+        }
+    }
+
+    if format.date {
+        let month = (digits[2] - b'0') * 10 + digits[3] - b'0';
+        let day = (digits[4] - b'0') * 10 + digits[5] - b'0';
+        //a day of 00 is the last day of the month
+        if !(1..=12).contains(&month) || day > 31 {
+            return invalid(String::from("is not a YYMMDD date."));
+        }
+    }
+
+    Ok(())
+}
+
+//the text split at each '(' that is not escaped as `\(`
+fn split_elements(text: &str) -> Vec<&str> {
+    let mut elements = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (idx, ch) in text.char_indices() {
+        if ch == '(' && !escaped {
+            elements.push(&text[start..idx]);
+            start = idx + 1;
+        }
+        escaped = ch == '\\' && !escaped;
+    }
+    elements.push(&text[start..]);
+
+    elements
+}
+
+/// validated GS1 application identifiers and their data fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementString {
+    elements: Vec<(String, String)>,
+}
+
+impl ElementString {
+    /// parses the human readable form, each AI in parentheses followed by its data field,
+    /// eg: `(01)09501101530003(17)251231(10)ABC`, a parenthesis in a data field being written `\(`
+    /// or `\)`, eg: `(10)A\(B\)`
+    pub fn parse(text: &str) -> Result<Self, QrError> {
+        let Some(rest) = text.strip_prefix('(') else {
+            return Err(QrError::InvalidGs1(String::from(
+                "the element string must start with an AI in parentheses, eg: (01).",
+            )));
+        };
+
+        let mut elements = Vec::new();
+        for element in split_elements(rest) {
+            let Some((ai, value)) = element.split_once(')') else {
+                return Err(QrError::InvalidGs1(format!(
+                    "'({}' has no closing parenthesis.",
+                    element
+                )));
+            };
+
+            if !(2..=4).contains(&ai.len()) || !ai.bytes().all(|byte| byte.is_ascii_digit()) {
+                return Err(QrError::InvalidGs1(format!(
+                    "'{}' is not an application identifier.",
+                    ai
+                )));
+            }
+
+            let value = value.replace("\\(", "(").replace("\\)", ")");
+            validate(ai, &value)?;
+            elements.push((String::from(ai), value));
+        }
+
+        Ok(Self { elements })
+    }
+
+    /// the application identifiers and their data fields, in order
+    pub fn elements(&self) -> &[(String, String)] {
+        &self.elements
+    }
+
+    /// the data to encode after FNC1, a `GS` ending every variable length field but the last
+    pub fn data(&self) -> String {
+        let mut data = String::new();
+        for (idx, (ai, value)) in self.elements.iter().enumerate() {
+            data.push_str(ai);
+            data.push_str(value);
+            if idx + 1 < self.elements.len() && needs_separator(ai) {
+                data.push(GS);
+            }
+        }
+
+        data
+    }
+}
+
+impl fmt::Display for ElementString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (ai, value) in &self.elements {
+            let value = value.replace('(', "\\(").replace(')', "\\)");
+            write!(f, "({}){}", ai, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+//primary keys a Digital Link path starts with and the key qualifiers that may follow them, in order
+const PRIMARY_KEYS: [(&str, &[&str]); 11] = [
+    ("00", &[]),
+    ("01", &["22", "10", "21"]),
+    ("401", &[]),
+    ("402", &[]),
+    ("414", &["254"]),
+    ("415", &["8020"]),
+    ("417", &[]),
+    ("8004", &[]),
+    ("8006", &["22", "10", "21"]),
+    ("8017", &[]),
+    ("8018", &[]),
+];
+
+//the key qualifiers of `ai`, `None` if it is not a primary key
+fn key_qualifiers(ai: &str) -> Option<&'static [&'static str]> {
+    PRIMARY_KEYS
+        .iter()
+        .find(|(key, _)| *key == ai)
+        .map(|(_, qualifiers)| *qualifiers)
+}
+
+//percent-encodes the characters of `value` a URI cannot hold as they are (the octets of their UTF-8),
+//and if `alphanumeric`, also the ones alphanumeric mode cannot hold (`%` and hex digits can)
+fn percent_encode(value: &str, alphanumeric: bool) -> String {
+    let mut encoded = String::new();
+    for ch in value.chars() {
+        let unreserved = ch.is_ascii_alphanumeric() || "-._~".contains(ch);
+        if unreserved && !(alphanumeric && alphanum_value(ch).is_none()) {
+            encoded.push(ch);
+        } else {
+            let mut buffer = [0; 4];
+            for byte in ch.encode_utf8(&mut buffer).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+    }
+
+    encoded
+}
+
+//the text of the percent-encoded UTF-8 `text`
+fn percent_decode(text: &str) -> Result<String, QrError> {
+    let mut parts = text.split('%');
+    let mut decoded = parts.next().unwrap_or_default().as_bytes().to_vec();
+    for part in parts {
+        let value = part
+            .get(..2)
+            .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        let Some(value) = value else {
+            return Err(QrError::InvalidGs1(format!(
+                "'%{}' is not a percent-encoded character.",
+                part
+            )));
+        };
+
+        decoded.push(value);
+        decoded.extend_from_slice(&part.as_bytes()[2..]);
+    }
+
+    String::from_utf8(decoded)
+        .map_err(|_| QrError::InvalidGs1(format!("'{}' is not percent-encoded UTF-8.", text)))
+}
+
+/// a GS1 Digital Link URI, the primary key and its key qualifiers in the path and the other AIs
+/// in the query, eg: `https://id.example.com/01/09501101530003/10/ABC?17=251231`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigitalLink {
+    //scheme, host and path prefix, without the trailing '/'
+    domain: String,
+    //the primary key, its key qualifiers in order, then the data attributes
+    elements: Vec<(String, String)>,
+    //how many of the elements go in the path
+    path: usize,
+}
+
+impl DigitalLink {
+    /// the URI of `element_string` on the resolver `domain` (eg: `https://id.gs1.org`),
+    /// failing unless it holds exactly one primary key (GTIN, SSCC, GLN, ...) and no AI twice
+    pub fn new(domain: &str, element_string: &ElementString) -> Result<Self, QrError> {
+        let domain = domain.trim_end_matches('/');
+        if authority_len(domain).is_none() {
+            return Err(QrError::InvalidGs1(format!(
+                "'{}' is not an http:// or https:// resolver domain.",
+                domain
+            )));
+        }
+
+        let elements = element_string.elements();
+        for (idx, (ai, _)) in elements.iter().enumerate() {
+            if elements[..idx].iter().any(|(prev, _)| prev == ai) {
+                return Err(QrError::InvalidGs1(format!("({}) is given twice.", ai)));
+            }
+        }
+
+        let mut keys = elements
+            .iter()
+            .filter(|(ai, _)| key_qualifiers(ai).is_some());
+        let (Some(key), None) = (keys.next(), keys.next()) else {
+            return Err(QrError::InvalidGs1(String::from(
+                "a Digital Link needs exactly one primary key, eg: (01).",
+            )));
+        };
+        let qualifiers = key_qualifiers(&key.0).unwrap_or_default();
+
+        let mut ordered = vec![key.clone()];
+        for qualifier in qualifiers {
+            ordered.extend(elements.iter().find(|(ai, _)| ai == qualifier).cloned());
+        }
+        let path = ordered.len();
+        ordered.extend(
+            elements
+                .iter()
+                .filter(|(ai, _)| {
+                    key_qualifiers(ai).is_none() && !qualifiers.contains(&ai.as_str())
+                })
+                .cloned(),
+        );
+
+        Ok(Self {
+            domain: String::from(domain),
+            elements: ordered,
+            path,
+        })
+    }
+
+    /// reads a Digital Link URI, validating the AIs of its path and query
+    /// (query parameters that are not AIs are skipped)
+    pub fn parse(uri: &str) -> Result<Self, QrError> {
+        let uri = uri.split('#').next().unwrap_or_default();
+        let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+        let Some(authority) = authority_len(path) else {
+            return Err(QrError::InvalidGs1(format!(
+                "'{}' is not an http:// or https:// URI.",
+                uri
+            )));
+        };
+
+        //the primary key is the first one the rest of the path pairs up after
+        let segments = path[authority..].split('/').skip(1).collect::<Vec<_>>();
+        let Some(start) = (0..segments.len()).find(|&idx| {
+            (segments.len() - idx) % 2 == 0 && key_qualifiers(segments[idx]).is_some()
+        }) else {
+            return Err(QrError::InvalidGs1(format!(
+                "'{}' has no primary key in its path.",
+                uri
+            )));
+        };
+
+        let mut elements = Vec::new();
+        for pair in segments[start..].chunks(2) {
+            elements.push((String::from(pair[0]), percent_decode(pair[1])?));
+        }
+        let path_len = elements.len();
+        for param in query.split('&') {
+            let (ai, value) = param.split_once('=').unwrap_or((param, ""));
+            if (2..=4).contains(&ai.len()) && ai.bytes().all(|byte| byte.is_ascii_digit()) {
+                elements.push((String::from(ai), percent_decode(value)?));
+            }
+        }
+
+        for (ai, value) in &elements {
+            validate(ai, value)?;
+        }
+
+        let prefix = segments[..start]
+            .iter()
+            .map(|segment| format!("/{}", segment))
+            .collect::<String>();
+        let domain = format!("{}{}", &path[..authority], prefix);
+        let in_path = elements[..path_len].to_vec();
+        let link = Self::new(&domain, &ElementString { elements })?;
+        if link.elements[..link.path] != in_path {
+            let (key, _) = &link.elements[0];
+            let qualifiers = key_qualifiers(key)
+                .unwrap_or_default()
+                .iter()
+                .map(|ai| format!("({})", ai))
+                .collect::<Vec<_>>();
+            return Err(QrError::InvalidGs1(if qualifiers.is_empty() {
+                format!("no AI may follow ({}) in the path.", key)
+            } else {
+                format!(
+                    "only {} may follow ({}) in the path, in that order.",
+                    qualifiers.join(", "),
+                    key
+                )
+            }));
+        }
+
+        Ok(link)
+    }
+
+    /// the AIs as an element string, the primary key first
+    pub fn element_string(&self) -> ElementString {
+        ElementString {
+            elements: self.elements.clone(),
+        }
+    }
+
+    /// the URI that gives the smallest symbol with `options`: the scheme and host in uppercase,
+    /// and values percent-encoded wherever that moves them to alphanumeric mode and saves bits
+    pub fn compact(&self, options: &QrOptions) -> String {
+        smallest(self.elements.len(), options, |alphanumeric| {
+            self.uri(true, alphanumeric)
+        })
+    }
+
+    //the URI, the scheme and host in uppercase if `upper`, each value percent-encoded
+    //for alphanumeric mode if its flag in `alphanumeric` is set
+    fn uri(&self, upper: bool, alphanumeric: &[bool]) -> String {
+        let mut uri = self.domain.clone();
+        if upper {
+            let len = authority_len(&uri).unwrap_or_default();
+            uri[..len].make_ascii_uppercase();
+        }
+
+        for (idx, ((ai, value), &alphanumeric)) in
+            self.elements.iter().zip(alphanumeric).enumerate()
+        {
+            let value = percent_encode(value, alphanumeric);
+            if idx < self.path {
+                uri.push_str(&format!("/{}/{}", ai, value));
+            } else {
+                let separator = if idx == self.path { '?' } else { '&' };
+                uri.push_str(&format!("{}{}={}", separator, ai, value));
+            }
+        }
+
+        uri
+    }
+}
+
+impl fmt::Display for DigitalLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.uri(false, &vec![false; self.elements.len()]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listed_application_identifiers() {
+        for element_string in [
+            "(253)4145678901238ABC",
+            "(255)4145678901238123",
+            "(8200)http://example.com",
+            "(7007)230101230131",
+            "(8003)01234567890128ABC",
+            "(4300)ACME(4307)DE(4321)1",
+            "(710)ABC123(715)X",
+        ] {
+            assert!(
+                ElementString::parse(element_string).is_ok(),
+                "{}",
+                element_string
+            );
+        }
+    }
+
+    #[test]
+    fn escaped_parentheses() {
+        let element_string = ElementString::parse("(10)A\\(B\\)(21)1)2").unwrap();
+        assert_eq!(
+            element_string.elements(),
+            [
+                (String::from("10"), String::from("A(B)")),
+                (String::from("21"), String::from("1)2")),
+            ]
+        );
+        assert_eq!(element_string.to_string(), "(10)A\\(B\\)(21)1\\)2");
+        assert!(ElementString::parse("(10)A(B)").is_err());
+    }
+
+    #[test]
+    fn key_check_digits() {
+        assert!(ElementString::parse("(253)4145678901234ABC").is_err());
+        assert!(ElementString::parse("(8003)A1234567890128").is_err());
+    }
+
+    #[test]
+    fn separators() {
+        let data = |element_string| ElementString::parse(element_string).unwrap().data();
+
+        //235 starts with the prefix 23 but its field is of variable length
+        assert_eq!(data("(235)ABC(10)X"), "235ABC\u{1D}10X");
+        assert_eq!(
+            data("(10)ABC(21)123(17)251231"),
+            "10ABC\u{1D}21123\u{1D}17251231"
+        );
+        //8001 is of fixed length but not of a predefined length prefix
+        assert_eq!(
+            data("(8001)12345678901234(10)X"),
+            "800112345678901234\u{1D}10X"
+        );
+        //no GS after the fields of predefined length, nor after the last field
+        assert_eq!(
+            data("(01)09501101530003(17)251231(3103)000189(10)ABC"),
+            "010950110153000317251231310300018910ABC"
+        );
+        assert_eq!(data("(10)ABC(235)XYZ"), "10ABC\u{1D}235XYZ");
+    }
+
+    #[test]
+    fn unlisted_application_identifiers() {
+        //04 is of predefined length, 499 is not
+        assert!(ElementString::parse("(04)1234567890123456").is_ok());
+        assert!(ElementString::parse("(04)123").is_err());
+        assert!(ElementString::parse("(499)xyz").is_ok());
+    }
+
+    fn link(element_string: &str) -> DigitalLink {
+        let element_string = ElementString::parse(element_string).unwrap();
+        DigitalLink::new("https://id.example.com/", &element_string).unwrap()
+    }
+
+    #[test]
+    fn digital_link_round_trip() {
+        let link = link("(17)251231(10)ABC(01)09501101530003(3103)000189");
+        //the primary key and its qualifiers in the path, the other AIs in the query
+        let uri = "https://id.example.com/01/09501101530003/10/ABC?17=251231&3103=000189";
+        assert_eq!(link.to_string(), uri);
+        assert_eq!(DigitalLink::parse(uri).unwrap(), link);
+        assert_eq!(
+            link.element_string().to_string(),
+            "(01)09501101530003(10)ABC(17)251231(3103)000189"
+        );
+
+        //a path before the primary key is part of the domain, other parameters and fragments are dropped
+        let uri = "https://example.com/products/01/09501101530003?utm=x&17=251231#top";
+        let link = DigitalLink::parse(uri).unwrap();
+        assert_eq!(
+            link.to_string(),
+            "https://example.com/products/01/09501101530003?17=251231"
+        );
+    }
+
+    #[test]
+    fn digital_link_percent_encoding() {
+        let link = link("(01)09501101530003(21)A/B%C(10)x&y");
+        let uri = "https://id.example.com/01/09501101530003/10/x%26y/21/A%2FB%25C";
+        assert_eq!(link.to_string(), uri);
+        assert_eq!(DigitalLink::parse(uri).unwrap(), link);
+        //escapes are case insensitive
+        let lower = "https://id.example.com/01/09501101530003/10/x%26y/21/A%2fB%25C";
+        assert_eq!(DigitalLink::parse(lower).unwrap(), link);
+
+        assert_eq!(percent_decode("%C3%A9t%C3%A9").unwrap(), "été");
+        assert_eq!(percent_encode("été", false), "%C3%A9t%C3%A9");
+        assert!(percent_decode("%C3").is_err());
+        assert!(percent_decode("%E9").is_err());
+        assert!(percent_decode("%G1").is_err());
+        assert!(percent_decode("50%").is_err());
+    }
+
+    #[test]
+    fn digital_link_compact() {
+        let link = link("(01)09501101530003(10)abc(17)251231");
+        let options = QrOptions::builder().build().unwrap();
+        let compact = link.compact(&options);
+        assert!(compact.starts_with("HTTPS://ID.EXAMPLE.COM/01/09501101530003/10/"));
+        let parsed = DigitalLink::parse(&compact).unwrap();
+        assert_eq!(parsed.element_string(), link.element_string());
+    }
+
+    #[test]
+    fn invalid_digital_links() {
+        for uri in [
+            //no primary key, a wrong check digit, qualifiers out of order or after the wrong key
+            "https://id.example.com/10/ABC",
+            "https://id.example.com/01/09501101530004",
+            "https://id.example.com/01/09501101530003/21/1/10/ABC",
+            "https://id.example.com/00/095011015300000003/10/ABC",
+            //an invalid date and a field too long in the query
+            "https://id.example.com/01/09501101530003?17=251399",
+            "https://id.example.com/01/09501101530003?20=123",
+            //a malformed escape, and not http
+            "https://id.example.com/01/09501101530003/10/A%2",
+            "ftp://id.example.com/01/09501101530003",
+        ] {
+            assert!(DigitalLink::parse(uri).is_err(), "{}", uri);
+        }
+
+        let two_keys = ElementString::parse("(01)09501101530003(00)095011015300000003").unwrap();
+        assert!(DigitalLink::new("https://id.example.com", &two_keys).is_err());
+        let no_key = ElementString::parse("(10)ABC").unwrap();
+        assert!(DigitalLink::new("https://id.example.com", &no_key).is_err());
+        let twice = ElementString::parse("(01)09501101530003(10)A(10)B").unwrap();
+        assert!(DigitalLink::new("https://id.example.com", &twice).is_err());
+        let element_string = ElementString::parse("(01)09501101530003").unwrap();
+        assert!(DigitalLink::new("id.example.com", &element_string).is_err());
+    }
+}
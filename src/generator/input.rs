@@ -0,0 +1,195 @@
+use super::Charset;
+use crate::error::QrError;
+
+/// the text of a data file written in `encoding`, unless it starts with a UTF-8 or UTF-16
+/// byte order mark, which sets the encoding and is dropped
+pub fn decode_input(data: &[u8], encoding: Charset) -> Result<String, QrError> {
+    if !cfg!(feature = "charsets") && !encoding.is_builtin() {
+        return Err(QrError::InvalidOption(format!(
+            "{} needs the charsets feature.",
+            encoding
+        )));
+    }
+
+    let (text, name) = match data {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => (String::from_utf8(rest.to_vec()).ok(), "UTF-8"),
+        [0xFF, 0xFE, rest @ ..] => (decode_utf16(rest, u16::from_le_bytes), "UTF-16LE"),
+        [0xFE, 0xFF, rest @ ..] => (decode_utf16(rest, u16::from_be_bytes), "UTF-16BE"),
+        _ => {
+            return encoding.decode_exact(data).ok_or_else(|| {
+                QrError::InvalidInput(format!("the input is not valid {}.", encoding))
+            })
+        }
+    };
+
+    text.ok_or_else(|| {
+        QrError::InvalidInput(format!(
+            "the input is not valid {} after its byte order mark.",
+            name
+        ))
+    })
+}
+
+/// true if `data` starts with a byte order mark `decode_input` reads
+pub(crate) fn has_bom(data: &[u8]) -> bool {
+    [&[0xEF, 0xBB, 0xBF][..], &[0xFF, 0xFE], &[0xFE, 0xFF]]
+        .iter()
+        .any(|bom| data.starts_with(bom))
+}
+
+//`None` for an odd number of octets or unpaired surrogates
+fn decode_utf16(data: &[u8], unit: fn([u8; 2]) -> u16) -> Option<String> {
+    if data.len() % 2 == 1 {
+        return None;
+    }
+
+    char::decode_utf16(data.chunks(2).map(|pair| unit([pair[0], pair[1]])))
+        .collect::<Result<String, _>>()
+        .ok()
+}
+
+/// decodes a hex literal such as "DE AD be ef", whitespace is ignored
+pub fn decode_hex(text: &str) -> Result<Vec<u8>, QrError> {
+    let digits = text
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .map(|ch| {
+            ch.to_digit(16)
+                .map(|x| x as u8)
+                .ok_or_else(|| QrError::InvalidInput(format!("'{}' is not a hex digit.", ch)))
+        })
+        .collect::<Result<Vec<u8>, QrError>>()?;
+
+    if digits.len() % 2 == 1 {
+        return Err(QrError::InvalidInput(String::from(
+            "hex literal must have an even number of digits.",
+        )));
+    }
+
+    Ok(digits.chunks(2).map(|pair| (pair[0] << 4) | pair[1]).collect())
+}
+
+/// lowercase hex digits of the bytes, without separators
+#[cfg(feature = "serde")]
+pub(crate) fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn base64_value(ch: char) -> Option<u8> {
+    match ch {
+        'A'..='Z' => Some(ch as u8 - b'A'),
+        'a'..='z' => Some(ch as u8 - b'a' + 26),
+        '0'..='9' => Some(ch as u8 - b'0' + 52),
+        '+' | '-' => Some(62),
+        '/' | '_' => Some(63),
+        _ => None,
+    }
+}
+
+/// decodes a base64 literal in the standard or url-safe alphabet, padding is optional
+pub fn decode_base64(text: &str) -> Result<Vec<u8>, QrError> {
+    let mut res = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for ch in text.chars().filter(|ch| !ch.is_whitespace()) {
+        if ch == '=' {
+            break;
+        }
+
+        let val = base64_value(ch)
+            .ok_or_else(|| QrError::InvalidInput(format!("'{}' is not a base64 character.", ch)))?;
+        buffer = (buffer << 6) | val as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            res.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    if bits >= 6 {
+        return Err(QrError::InvalidInput(String::from(
+            "base64 literal is truncated.",
+        )));
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_encodings() {
+        assert_eq!(decode_input(b"caf\xE9", Charset::Latin1).unwrap(), "café");
+        assert_eq!(
+            decode_input("café".as_bytes(), Charset::Utf8).unwrap(),
+            "café"
+        );
+        assert!(matches!(
+            decode_input(b"caf\xE9", Charset::Utf8),
+            Err(QrError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "charsets")]
+    fn other_encodings() {
+        let shift_jis = [0x93, 0xFA, 0x96, 0x7B, 0x8C, 0xEA];
+        assert_eq!(
+            decode_input(&shift_jis, Charset::ShiftJis).unwrap(),
+            "日本語"
+        );
+        let windows_1251 = [0xCF, 0xF0, 0xE8, 0xE2, 0xE5, 0xF2];
+        assert_eq!(
+            decode_input(&windows_1251, Charset::Windows1251).unwrap(),
+            "Привет"
+        );
+
+        //a lead byte without the trail byte it needs
+        assert!(matches!(
+            decode_input(&shift_jis[..5], Charset::ShiftJis),
+            Err(QrError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(not(feature = "charsets"))]
+    fn other_encodings_need_the_feature() {
+        assert!(matches!(
+            decode_input(b"abc", Charset::ShiftJis),
+            Err(QrError::InvalidOption(_))
+        ));
+    }
+
+    #[test]
+    fn byte_order_marks() {
+        assert_eq!(
+            decode_input(b"\xEF\xBB\xBFcaf\xC3\xA9", Charset::Latin1).unwrap(),
+            "café"
+        );
+        assert_eq!(
+            decode_input(b"\xFF\xFEc\0a\0f\0\xE9\0", Charset::Latin1).unwrap(),
+            "café"
+        );
+        assert_eq!(
+            decode_input(b"\xFE\xFF\0c\0a\0f\0\xE9", Charset::Latin1).unwrap(),
+            "café"
+        );
+
+        //an odd number of octets, an unpaired surrogate and invalid UTF-8 after the mark
+        for data in [
+            &b"\xFF\xFEc\0a"[..],
+            b"\xFF\xFE\x00\xD8",
+            b"\xEF\xBB\xBFcaf\xE9",
+        ] {
+            assert!(matches!(
+                decode_input(data, Charset::Utf8),
+                Err(QrError::InvalidInput(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,719 @@
+use core::ops::Range;
+
+use super::{
+    data::{get_format_ecc, obtain_qr_alignment},
+    ecc::version_information_process,
+    ECCLevel,
+};
+
+fn mask0(i: usize, j: usize) -> bool {
+    (i + j).is_multiple_of(2)
+}
+
+fn mask1(i: usize, _j: usize) -> bool {
+    i.is_multiple_of(2)
+}
+
+fn mask2(_i: usize, j: usize) -> bool {
+    j.is_multiple_of(3)
+}
+
+fn mask3(i: usize, j: usize) -> bool {
+    (i + j).is_multiple_of(3)
+}
+
+fn mask4(i: usize, j: usize) -> bool {
+    ((i / 2) + (j / 3)).is_multiple_of(2)
+}
+
+fn mask5(i: usize, j: usize) -> bool {
+    ((i * j) % 2) + ((i * j) % 3) == 0
+}
+
+fn mask6(i: usize, j: usize) -> bool {
+    (((i * j) % 2) + ((i * j) % 3)).is_multiple_of(2)
+}
+
+fn mask7(i: usize, j: usize) -> bool {
+    (((i + j) % 2) + ((i * j) % 3)).is_multiple_of(2)
+}
+
+pub const MASKS: [fn(usize, usize) -> bool; 8] = [mask0, mask1, mask2, mask3, mask4, mask5, mask6, mask7];
+
+//dark, light, 3 dark, light, dark: the 1:1:3:1:1 ratio of a finder pattern
+const FINDER_LIKE: [bool; 7] = [true, false, true, true, true, false, true];
+
+/// what a module of the symbol is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleKind {
+    Finder,
+    /// the light border around each finder pattern
+    Separator,
+    Timing,
+    Alignment,
+    /// format information, including the single dark module
+    Format,
+    /// version information (version 7 and above)
+    Version,
+    Data,
+    Ecc,
+    /// leftover bits after the last codeword
+    Remainder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Module {
+    pub x: usize,
+    pub y: usize,
+    pub dark: bool,
+    pub kind: ModuleKind,
+}
+
+/// the penalty points of a masked symbol under each of the four rules of ISO/IEC 18004 (7.8.3.1),
+/// the mask with the lowest total is chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Penalty {
+    rules: [usize; 4],
+}
+
+impl Penalty {
+    /// the points of rules 1 to 4:
+    /// - runs of 5 or more modules of the same colour in a row or column, 3 plus 1 for each module past 5
+    /// - 2x2 blocks of the same colour, 3 each (blocks may overlap)
+    /// - 1:1:3:1:1 (dark, light, dark, light, dark) patterns in a row or column with 4 light modules
+    ///   before or after them, 40 each
+    /// - 10 for every full 5% the share of dark modules is away from half
+    pub fn rules(&self) -> [usize; 4] {
+        self.rules
+    }
+
+    pub fn total(&self) -> usize {
+        self.rules.iter().sum()
+    }
+}
+
+/// the modules of a symbol, row by row with 8 modules per byte (most significant bit first),
+/// each row starting on a new byte
+/// the storage is anything that derefs to bytes, a Vec or a caller-supplied slice
+#[derive(Debug, Clone)]
+pub struct Matrix<B> {
+    bits: B,
+    layout: Layout,
+}
+
+/// where the function patterns and the encoding region of a version are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    size: usize,
+    version: u8,
+}
+
+/// number of modules on each side of a symbol
+pub const fn side(version: u8) -> usize {
+    21 + ((version as usize) - 1) * 4
+}
+
+/// number of bytes of each row
+pub const fn stride(size: usize) -> usize {
+    size.div_ceil(8)
+}
+
+/// number of bytes needed to hold a symbol of `version`
+pub const fn matrix_len(version: u8) -> usize {
+    side(version) * stride(side(version))
+}
+
+impl Layout {
+    pub const fn new(version: u8) -> Self {
+        Self {
+            size: side(version),
+            version,
+        }
+    }
+
+    //centres of the alignment patterns that do not overlap the finders
+    fn alignment_centres(self) -> impl Iterator<Item = (usize, usize)> {
+        let align_pattern = obtain_qr_alignment(self.version);
+        let size = self.size;
+        align_pattern
+            .iter()
+            .flat_map(move |&px| align_pattern.iter().map(move |&py| (px as usize, py as usize)))
+            .filter(move |&(px, py)| {
+                !((py - 2 < 7 && (px - 2 < 7 || px + 2 >= size - 7)) || (px - 2 < 7 && py + 2 >= size - 7))
+            })
+    }
+
+    /// the function pattern (x, y) belongs to, Data for the encoding region
+    #[cfg(feature = "std")]
+    pub fn pattern_kind(self, x: usize, y: usize) -> ModuleKind {
+        let far = self.size - 7;
+        if (x < 7 || x >= far) && y < 7 || x < 7 && y >= far {
+            return ModuleKind::Finder;
+        }
+
+        if (x < 8 || x >= far - 1) && y < 8 || x < 8 && y >= far - 1 {
+            return ModuleKind::Separator;
+        }
+
+        if (x == 8 && (y <= 8 || y >= far - 1)) || (y == 8 && (x <= 8 || x >= far - 1)) {
+            return if x == 6 || y == 6 {
+                ModuleKind::Timing
+            } else {
+                ModuleKind::Format
+            };
+        }
+
+        if self.version >= 7
+            && ((y < 6 && x >= self.size - 11 && x < far - 1)
+                || (x < 6 && y >= self.size - 11 && y < far - 1))
+        {
+            return ModuleKind::Version;
+        }
+
+        if self
+            .alignment_centres()
+            .any(|(px, py)| x.abs_diff(px) <= 2 && y.abs_diff(py) <= 2)
+        {
+            return ModuleKind::Alignment;
+        }
+
+        if x == 6 || y == 6 {
+            return ModuleKind::Timing;
+        }
+
+        ModuleKind::Data
+    }
+
+    //all the patterns except timing
+    pub fn is_reserved(self, x: usize, y: usize) -> bool {
+        if x >= self.size || y >= self.size {
+            return true; //out of bounds
+        }
+
+        //finders only
+        if x < 7 && y < 7 {
+            return true;
+        }
+
+        if x >= self.size - 7 && y < 7 {
+            return true;
+        }
+
+        if x < 7 && y >= self.size - 7 {
+            return true;
+        }
+
+        self.alignment_centres()
+            .any(|(px, py)| (x >= px - 2 && x <= px + 2) && (y >= py - 2 && y <= py + 2))
+    }
+
+    //is_reserved + format information
+    fn is_occupied_without_version(self, x: usize, y: usize) -> bool {
+        if y == 6 || x == 6 {
+            return true; //timing
+        }
+
+        if self.is_reserved(x, y) {
+            return true;
+        }
+
+        //finder & format
+        if x < 9 && y < 9 {
+            return true;
+        }
+
+        if x >= self.size - 8 && y < 9 {
+            return true;
+        }
+
+        if x < 9 && y >= self.size - 8 {
+            return true;
+        }
+
+        false
+    }
+
+    //is_occupied but with version
+    pub fn is_occupied(self, x: usize, y: usize) -> bool {
+        if self.is_occupied_without_version(x, y) {
+            return true;
+        }
+
+        if self.version >= 7 {
+            if y < 6 && x >= self.size - 11 {
+                return true;
+            }
+
+            if x < 6 && y >= self.size - 11 {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// the modules of the encoding region in the order the codeword bits are placed
+    pub fn data_positions(self) -> impl Iterator<Item = (usize, usize)> {
+        let mut px = self.size - 1;
+        let mut py = self.size - 1;
+        let mut move_up = true;
+        let mut done = false;
+
+        //each step visits the right then the left module of a column pair
+        core::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+
+            let step = [(px, py), (px - 1, py)];
+
+            if move_up {
+                if py == 0 {
+                    if px <= 2 {
+                        done = true;
+                    } else {
+                        move_up = false;
+                        px -= 2;
+                    }
+                } else {
+                    py -= 1;
+                }
+            } else if py == self.size - 1 {
+                if px <= 2 {
+                    done = true;
+                } else {
+                    move_up = true;
+                    px -= 2;
+                }
+            } else {
+                py += 1;
+            }
+
+            if px == 6 {
+                px = 5;
+            }
+
+            Some(step)
+        })
+        .flatten()
+        .filter(move |&(x, y)| !self.is_occupied(x, y))
+    }
+}
+
+impl<B: AsRef<[u8]>> Matrix<B> {
+    /// `bits` must hold at least `matrix_len(version)` bytes, only those are used
+    pub fn new(bits: B, version: u8) -> Self {
+        Self {
+            bits,
+            layout: Layout::new(version),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.layout.size
+    }
+
+    pub fn version(&self) -> u8 {
+        self.layout.version
+    }
+
+    #[cfg(feature = "std")]
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// the packed rows
+    pub fn bytes(&self) -> &[u8] {
+        &self.bits.as_ref()[..matrix_len(self.layout.version)]
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        let byte = self.bits.as_ref()[y * stride(self.layout.size) + x / 8];
+        (byte >> (7 - x % 8)) & 1 == 1
+    }
+
+    /// the penalty of the current (masked) symbol under the four rules, every module counted
+    pub fn penalty(&self) -> Penalty {
+        let size = self.layout.size;
+        let mut runs = 0;
+        let mut patterns = 0;
+
+        //rules 1 and 3 along every row and every column
+        for line in 0..size {
+            for horizontal in [true, false] {
+                let cell = |idx: usize| {
+                    if horizontal {
+                        self.get(idx, line)
+                    } else {
+                        self.get(line, idx)
+                    }
+                };
+
+                let mut run = 1;
+                for idx in 1..=size {
+                    if idx < size && cell(idx) == cell(idx - 1) {
+                        run += 1;
+                        continue;
+                    }
+
+                    if run >= 5 {
+                        runs += 3 + run - 5;
+                    }
+                    run = 1;
+                }
+
+                //the modules past the edges are the light quiet zone
+                let light =
+                    |range: Range<usize>| range.take_while(|&idx| idx < size).all(|idx| !cell(idx));
+                for idx in 0..=size - 7 {
+                    if (0..7).all(|i| cell(idx + i) == FINDER_LIKE[i])
+                        && (light(idx.saturating_sub(4)..idx) || light(idx + 7..idx + 11))
+                    {
+                        patterns += 40;
+                    }
+                }
+            }
+        }
+
+        let blocks = (0..size - 1)
+            .flat_map(|y| (0..size - 1).map(move |x| (x, y)))
+            .filter(|&(x, y)| {
+                let cell = self.get(x, y);
+                self.get(x + 1, y) == cell
+                    && self.get(x, y + 1) == cell
+                    && self.get(x + 1, y + 1) == cell
+            })
+            .count();
+
+        //10 points for every full 5% away from half dark
+        let dark = (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.get(x, y))
+            .count();
+        let total = size * size;
+        let balance = 10 * ((20 * dark).abs_diff(10 * total) / total);
+
+        Penalty {
+            rules: [runs, 3 * blocks, patterns, balance],
+        }
+    }
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>> Matrix<B> {
+    pub fn set(&mut self, x: usize, y: usize, flag: bool) {
+        let idx = y * stride(self.layout.size) + x / 8;
+        let byte = &mut self.bits.as_mut()[idx];
+        *byte = *byte & !(1 << (7 - x % 8)) | ((flag as u8) << (7 - x % 8));
+    }
+
+    fn add_find_pattern(&mut self) {
+        for x in 0..7 {
+            for y in 0..7 {
+                if ((y == 1 || y == 5) && (1..=5).contains(&x))
+                    || ((x == 1 || x == 5) && (1..=5).contains(&y))
+                {
+                    continue;
+                }
+
+                self.set(x, y, true);
+                self.set(x, y + self.layout.size - 7, true);
+                self.set(x + self.layout.size - 7, y, true);
+            }
+        }
+    }
+
+    fn add_timing_pattern(&mut self) {
+        for x in 8..(self.layout.size - 8) {
+            self.set(x, 6, x % 2 == 0);
+        }
+
+        for y in 8..(self.layout.size - 8) {
+            self.set(6, y, y % 2 == 0);
+        }
+    }
+
+    fn add_alignment_pattern(&mut self) {
+        for (x, y) in self.layout.alignment_centres() {
+            for i in 0..=4 {
+                self.set(x + i - 2, y + 2, true);
+                self.set(x + i - 2, y - 2, true);
+            }
+
+            for i in 0..=2 {
+                self.set(x - 2, y + i - 1, true);
+                self.set(x + 2, y + i - 1, true);
+            }
+
+            self.set(x, y, true);
+        }
+    }
+
+    /// clears the matrix, draws the function patterns and places the codewords in the encoding region
+    pub fn generate(&mut self, codewords: impl Iterator<Item = u8>) {
+        let len = matrix_len(self.layout.version);
+        self.bits.as_mut()[..len].fill(0);
+
+        self.add_find_pattern();
+        self.add_timing_pattern();
+        self.add_alignment_pattern();
+
+        let bits = codewords.flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1));
+        for ((x, y), bit) in self.layout.data_positions().zip(bits) {
+            self.set(x, y, bit);
+        }
+    }
+
+    fn add_format_symbols(&mut self, ecc: &ECCLevel, mask: u8) {
+        let size = self.layout.size;
+        let mut fmt = 0u16;
+
+        fmt |= (match ecc {
+            ECCLevel::Low => 0b01,
+            ECCLevel::Medium => 0b00,
+            ECCLevel::Quartile => 0b11,
+            ECCLevel::High => 0b10,
+        }) << 3;
+
+        fmt |= mask as u16;
+
+        let err = get_format_ecc(fmt as u8);
+        fmt = (fmt << 10) | err;
+
+        fmt ^= 0b101010000010010;
+
+        //top left
+        for y in 0..=5 {
+            self.set(8, y, (fmt >> y) & 1 == 1);
+        }
+
+        self.set(8, 7, (fmt >> 6) & 1 == 1);
+        self.set(8, 8, (fmt >> 7) & 1 == 1);
+        self.set(7, 8, (fmt >> 8) & 1 == 1);
+
+        for x in (0..=5).rev() {
+            self.set(5 - x, 8, (fmt >> (9 + x)) & 1 == 1);
+        }
+
+        //top right
+        for x in 0..=7 {
+            self.set(size - x - 1, 8, (fmt >> x) & 1 == 1);
+        }
+
+        //bottom left
+        for y in (0..=6).rev() {
+            self.set(8, size - y - 1, (fmt >> (14 - y)) & 1 == 1);
+        }
+
+        //the single compulsory black module
+        self.set(8, size - 8, true);
+
+        //version information
+        if self.layout.version >= 7 {
+            let vers_info = version_information_process(self.layout.version);
+
+            let mut idx = 0;
+            let mut px = size - 11;
+            let mut py = 0;
+            while idx < 18 {
+                self.set(px, py, (vers_info >> idx) & 1 == 1);
+                self.set(py, px, (vers_info >> idx) & 1 == 1);
+                px += 1;
+                if px == size - 8 {
+                    px = size - 11;
+                    py += 1;
+                }
+                idx += 1;
+            }
+        }
+    }
+
+    //flips the encoding region where the mask is set, applying it twice undoes it
+    fn apply_mask(&mut self, mask: u8) {
+        let func = MASKS[mask as usize];
+        for y in 0..self.layout.size {
+            for x in 0..self.layout.size {
+                if !self.layout.is_occupied(x, y) && func(y, x) {
+                    let cell = self.get(x, y);
+                    self.set(x, y, !cell);
+                }
+            }
+        }
+    }
+
+    /// the penalty of every mask pattern, each with its own format information,
+    /// the matrix is left unmasked
+    pub fn penalties(&mut self, ecc: &ECCLevel) -> [Penalty; 8] {
+        core::array::from_fn(|mask| {
+            self.add_format_symbols(ecc, mask as u8);
+            self.apply_mask(mask as u8);
+            let penalty = self.penalty();
+            self.apply_mask(mask as u8);
+            penalty
+        })
+    }
+
+    /// masks the matrix and writes the format information, returns the mask used and the penalty of every mask
+    /// `forced` pins the mask pattern, otherwise the one with the least penalty (the first on a tie) is chosen
+    pub fn mask(&mut self, ecc: &ECCLevel, forced: Option<u8>) -> (u8, [Penalty; 8]) {
+        let penalties = self.penalties(ecc);
+        let best = forced.unwrap_or_else(|| {
+            (0..MASKS.len() as u8)
+                .min_by_key(|&mask| penalties[mask as usize].total())
+                .unwrap_or(0)
+        });
+
+        self.add_format_symbols(ecc, best);
+        self.apply_mask(best);
+        (best, penalties)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Symbol = Matrix<[u8; matrix_len(1)]>;
+
+    //a version 1 matrix with no penalty at all: 221 dark modules of 441, none next to one of its colour
+    fn checkerboard() -> Symbol {
+        let mut matrix = Matrix::new([0; matrix_len(1)], 1);
+        for y in 0..21 {
+            for x in 0..21 {
+                matrix.set(x, y, (x + y) % 2 == 0);
+            }
+        }
+
+        matrix
+    }
+
+    //writes `modules` ('#' dark, '.' light) from (x, y) rightwards, or downwards if not `horizontal`
+    fn draw(matrix: &mut Symbol, x: usize, y: usize, horizontal: bool, modules: &str) {
+        for (idx, module) in modules.chars().enumerate() {
+            let (x, y) = if horizontal {
+                (x + idx, y)
+            } else {
+                (x, y + idx)
+            };
+            matrix.set(x, y, module == '#');
+        }
+    }
+
+    #[test]
+    fn checkerboard_has_no_penalty() {
+        assert_eq!(checkerboard().penalty().rules(), [0; 4]);
+    }
+
+    #[test]
+    fn rule_1_runs() {
+        //a run ends where the next module is of the other colour or at the edge
+        for (modules, points) in [
+            ("#####.", 3),
+            ("######.", 4),
+            ("#######.", 5),
+            (".....#", 3),
+            ("..........#", 8),
+            ("####.", 0),
+        ] {
+            for horizontal in [true, false] {
+                //along the row starting at (0, 10) or the column starting at (10, 0)
+                let (x, y) = if horizontal { (0, 10) } else { (10, 0) };
+                let mut matrix = checkerboard();
+                draw(&mut matrix, x, y, horizontal, modules);
+                assert_eq!(matrix.penalty().rules()[0], points, "{modules}");
+            }
+        }
+
+        //a run along a whole row of 21 modules
+        let mut matrix = checkerboard();
+        draw(&mut matrix, 0, 4, true, &"#".repeat(21));
+        assert_eq!(matrix.penalty().rules()[0], 3 + 16);
+    }
+
+    #[test]
+    fn rule_2_blocks() {
+        //a 2x2 block, then a 3x2 one (2 blocks) and a 3x3 one (4 overlapping blocks)
+        for (width, height, blocks) in [(2, 2, 1), (3, 2, 2), (3, 3, 4)] {
+            for dark in [true, false] {
+                let mut matrix = checkerboard();
+                for y in 0..height {
+                    for x in 0..width {
+                        matrix.set(10 + x, 10 + y, dark);
+                    }
+                }
+                assert_eq!(matrix.penalty().rules()[1], 3 * blocks);
+            }
+        }
+    }
+
+    #[test]
+    fn rule_3_finder_like_patterns() {
+        let mut light = Matrix::new([0; matrix_len(1)], 1);
+        //4 light modules after the pattern
+        draw(&mut light, 3, 10, true, "#.###.#....#");
+        assert_eq!(light.penalty().rules()[2], 40);
+
+        //4 light modules before it, and the pattern in a column
+        let mut light = Matrix::new([0; matrix_len(1)], 1);
+        draw(&mut light, 10, 2, false, "#....#.###.#");
+        assert_eq!(light.penalty().rules()[2], 40);
+
+        //the quiet zone counts as light past either edge
+        for (x, modules) in [(0, "#.###.##"), (13, "##.###.#")] {
+            let mut matrix = checkerboard();
+            draw(&mut matrix, x, 10, true, modules);
+            assert_eq!(matrix.penalty().rules()[2], 40, "{modules}");
+        }
+
+        //3 light modules on either side are not enough
+        let mut matrix = Matrix::new([0; matrix_len(1)], 1);
+        draw(&mut matrix, 3, 10, true, "#...#.###.#...#");
+        assert_eq!(matrix.penalty().rules()[2], 0);
+    }
+
+    #[test]
+    fn rule_4_dark_share() {
+        //441 modules: 199 (45.1%) to 242 (54.9%) dark modules are within 5% of half, 265 (60.1%) is 10% away
+        for (dark, points) in [
+            (0, 100),
+            (198, 10),
+            (199, 0),
+            (220, 0),
+            (242, 0),
+            (243, 10),
+            (264, 10),
+            (265, 20),
+            (441, 100),
+        ] {
+            let mut matrix = Matrix::new([0; matrix_len(1)], 1);
+            for idx in 0..dark {
+                matrix.set(idx % 21, idx / 21, true);
+            }
+            assert_eq!(matrix.penalty().rules()[3], points, "{dark} dark modules");
+        }
+    }
+
+    #[test]
+    fn forced_mask() {
+        let generate = || {
+            let mut matrix = Matrix::new([0; matrix_len(1)], 1);
+            matrix.generate((0..26u8).map(|idx| idx.wrapping_mul(37)));
+            matrix
+        };
+
+        let (best, penalties) = generate().mask(&ECCLevel::Medium, None);
+        let least = penalties.iter().map(Penalty::total).min().unwrap();
+        assert_eq!(penalties[best as usize].total(), least);
+        assert!(penalties[..best as usize]
+            .iter()
+            .all(|penalty| penalty.total() > least));
+
+        for mask in 0..8 {
+            let mut matrix = generate();
+            let (used, forced) = matrix.mask(&ECCLevel::Medium, Some(mask));
+            assert_eq!(used, mask);
+            assert_eq!(forced, penalties);
+            //the matrix is left masked with the pinned pattern
+            assert_eq!(matrix.penalty(), penalties[mask as usize]);
+        }
+    }
+}
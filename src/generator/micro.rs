@@ -0,0 +1,572 @@
+#[cfg(any(feature = "render-png", feature = "render-jpeg"))]
+use image::{ImageBuffer, Rgba};
+
+#[cfg(any(feature = "render-png", feature = "render-jpeg"))]
+use super::qr::render;
+use super::{
+    bitstream::BitStream,
+    data::{get_format_ecc, micro_block},
+    ecc::ErrorCorrection,
+    encoder::is_kanji,
+    matrix::{stride, MASKS},
+    Charset, ECCLevel, Generator, Mode, Segment,
+};
+use crate::error::QrError;
+
+//the QR code masks Micro QR uses, in the order of their Micro QR numbers
+const MICRO_MASKS: [usize; 4] = [1, 4, 6, 7];
+
+/// number of modules on each side of Micro QR version `version` (1 to 4)
+const fn micro_side(version: u8) -> usize {
+    9 + 2 * version as usize
+}
+
+/// size of the whole header and of the character count indicator of `mode` in M`version`,
+/// `None` if the version has no such mode
+///
+/// the mode indicator is the number of the mode (see `Mode::from_index`) in `version - 1` bits
+fn header(mode: Mode, version: u8) -> Option<(usize, usize)> {
+    let count_bits = match mode {
+        Mode::Numeric => [3, 4, 5, 6],
+        Mode::Alphanumeric => [0, 3, 4, 5],
+        Mode::Byte => [0, 0, 4, 5],
+        Mode::Kanji => [0, 0, 3, 4],
+        Mode::Hanzi => [0; 4],
+    }[(version - 1) as usize];
+
+    (count_bits > 0).then_some((version as usize - 1 + count_bits, count_bits))
+}
+
+/// true if (x, y) is part of the finder, its separator, the format information or the timing patterns
+fn is_function(x: usize, y: usize) -> bool {
+    x == 0 || y == 0 || (x <= 8 && y <= 8)
+}
+
+impl Generator {
+    /// the first of `versions` holding the payload and the segments it is written in, for the symbols
+    /// without ECI or FNC1 (`symbol` names them in errors), whose byte mode text can only be Latin-1
+    ///
+    /// `versions` gives each version, the header of each mode in `Mode::ALL` order (see `header`)
+    /// and the number of data bits it holds
+    pub(super) fn plan_latin1(
+        &self,
+        symbol: &str,
+        versions: impl Iterator<Item = (u8, [Option<(usize, usize)>; 5], usize)>,
+    ) -> Result<(u8, Vec<Segment>), QrError> {
+        if let Some(charset) = self
+            .options
+            .charset()
+            .filter(|&charset| charset != Charset::Latin1)
+        {
+            return Err(QrError::InvalidOption(format!(
+                "{} has no ECI, byte mode text can only be ISO-8859-1 but given {}.",
+                symbol, charset
+            )));
+        }
+
+        if self.options.fnc1().is_some() {
+            return Err(QrError::InvalidOption(format!(
+                "{} has no FNC1 mode.",
+                symbol
+            )));
+        }
+
+        let text = self.text();
+        let fixed = match text {
+            Some("") => return Err(QrError::EmptyInput),
+            Some(text) => {
+                if let Some(ch) = text
+                    .chars()
+                    .find(|&ch| !Charset::Latin1.accepts(ch) && !is_kanji(ch))
+                {
+                    return Err(QrError::UnrepresentableCharacter {
+                        ch,
+                        charset: Charset::Latin1,
+                    });
+                }
+
+                Vec::new()
+            }
+            None => {
+                let segments = self.fixed_segments();
+                if segments.iter().all(|seg| seg.data().is_empty()) {
+                    return Err(QrError::EmptyInput);
+                }
+
+                for seg in &segments {
+                    if let (Mode::Byte, Some(text)) = (seg.mode(), seg.text()) {
+                        Charset::Latin1.check(text)?;
+                    }
+                }
+
+                segments
+            }
+        };
+
+        //octets of each character in byte mode, `None` outside of Latin-1
+        let octets = text.map_or_else(Vec::new, |text| {
+            text.chars()
+                .map(|ch| Charset::Latin1.char_len(ch))
+                .collect()
+        });
+
+        for (version, headers, capacity) in versions {
+            let segments = match text {
+                Some(text) => {
+                    //small versions lack some modes, every character needs one of those left
+                    let writable = text.chars().zip(&octets).all(|(ch, len)| {
+                        Mode::ALL.iter().zip(&headers).any(|(&mode, header)| {
+                            header.is_some()
+                                && match mode {
+                                    Mode::Byte => len.is_some(),
+                                    _ => mode.accepts(ch),
+                                }
+                        })
+                    });
+                    if !writable {
+                        continue;
+                    }
+
+                    Self::segment(text, &octets, headers, false)?
+                }
+                None => fixed.clone(),
+            };
+
+            let bits = segments
+                .iter()
+                .map(|seg| {
+                    let count = seg.count(Charset::Latin1);
+                    headers[seg.mode() as usize]
+                        .filter(|&(_, count_bits)| count < 1 << count_bits)
+                        .map(|(header, _)| header + seg.mode().data_bits(count))
+                })
+                .sum::<Option<usize>>();
+
+            if bits.is_some_and(|bits| bits <= capacity) {
+                return Ok((version, segments));
+            }
+        }
+
+        Err(QrError::NoVersionFits)
+    }
+
+    /// the smallest Micro QR version holding the payload and the segments it is written in
+    fn plan_micro(&self) -> Result<(u8, Vec<Segment>), QrError> {
+        let ecc = self.options.ecc();
+        if ecc == ECCLevel::High {
+            return Err(QrError::InvalidOption(String::from(
+                "Micro QR has no error correction level H.",
+            )));
+        }
+
+        self.plan_latin1(
+            "Micro QR",
+            (1..=4).filter_map(|version| {
+                let (capacity, _) = micro_block(&ecc, version)?;
+                Some((
+                    version,
+                    Mode::ALL.map(|mode| header(mode, version)),
+                    capacity,
+                ))
+            }),
+        )
+    }
+
+    /// encodes the payload into the smallest Micro QR symbol (M1 to M4) holding it
+    ///
+    /// Micro QR has no ECI, FNC1 or structured append, so byte mode text is always Latin-1.
+    /// There is no level H and only M4 has level Q, M1 merely detects errors and is only used at level L.
+    /// A pinned mask must be one of the four Micro QR masks (0-3).
+    pub fn run_micro(self) -> Result<MicroQRCode, QrError> {
+        if let Some(mask) = self.options.mask().filter(|&mask| mask > 3) {
+            return Err(QrError::InvalidOption(format!(
+                "Micro QR mask must be 0 to 3 inclusive but given '{}'.",
+                mask
+            )));
+        }
+
+        let (version, segments) = self.plan_micro()?;
+        let ecc = self.options.ecc();
+        let (capacity, err_len) = micro_block(&ecc, version).ok_or(QrError::NoVersionFits)?;
+
+        //M1 and M3 end on a 4 bit data codeword, held in the high half of a byte
+        let data_len = capacity.div_ceil(8);
+        let mut codewords = vec![0u8; data_len + err_len];
+        let mut stream = BitStream::new(&mut codewords[..data_len]);
+
+        for segment in &segments {
+            let count = segment.count(Charset::Latin1);
+            let (_, count_bits) = header(segment.mode(), version).ok_or(QrError::NoVersionFits)?;
+            stream.push_bits(segment.mode() as u8, version - 1);
+            stream.push_bits_big(count, count_bits as u8);
+            segment.encode_data(&mut stream, Charset::Latin1)?;
+        }
+
+        if stream.size() > capacity {
+            return Err(QrError::CapacityExceeded {
+                bits: stream.size(),
+                capacity,
+            });
+        }
+
+        //the terminator is cut short if the symbol is full
+        let terminator = (2 * version as usize + 1).min(capacity - stream.size());
+        stream.push_bits(0, terminator as u8);
+
+        //pad codewords fill the whole data codewords, a last 4 bit codeword is left at 0
+        let used = stream.len().min(capacity / 8);
+        for (idx, byte) in codewords[used..capacity / 8].iter_mut().enumerate() {
+            *byte = if idx % 2 == 0 { 0xEC } else { 0x11 };
+        }
+
+        let (data, err) = codewords.split_at_mut(data_len);
+        ErrorCorrection::new().calculate(data, err);
+
+        let bits = (0..capacity)
+            .map(|idx| (codewords[idx / 8] >> (7 - idx % 8)) & 1 == 1)
+            .chain(
+                codewords[data_len..]
+                    .iter()
+                    .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1)),
+            );
+
+        let mut symbol = MicroQRCode::new(version, (version > 1).then_some(ecc), segments);
+        symbol.generate(bits);
+        symbol.choose_mask(self.options.mask());
+        Ok(symbol)
+    }
+}
+
+/// a Micro QR symbol (M1 to M4), a single finder pattern and a 2 module quiet zone
+/// for data too small to be worth a 21x21 QR code
+pub struct MicroQRCode {
+    version: u8,
+    ecc: Option<ECCLevel>,
+    mask: u8,
+    //row by row, 8 modules per byte as in `QRCode::as_bytes`
+    rows: Vec<u8>,
+    segments: Vec<Segment>,
+}
+
+impl MicroQRCode {
+    fn new(version: u8, ecc: Option<ECCLevel>, segments: Vec<Segment>) -> Self {
+        let size = micro_side(version);
+        Self {
+            version,
+            ecc,
+            mask: 0,
+            rows: vec![0; size * stride(size)],
+            segments,
+        }
+    }
+
+    /// number of modules on each side of the symbol (quiet zone excluded), 11 to 17
+    pub fn size(&self) -> usize {
+        micro_side(self.version)
+    }
+
+    /// 1 to 4 for M1 to M4
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// `None` for M1, which only detects errors
+    pub fn ecc_level(&self) -> Option<ECCLevel> {
+        self.ecc
+    }
+
+    /// Micro QR mask pattern (0-3) chosen by the evaluation
+    pub fn mask(&self) -> u8 {
+        self.mask
+    }
+
+    /// the segments the data was encoded in
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// the rows of the symbol, 8 modules per byte (most significant bit first),
+    /// every row starts on a new byte
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.rows
+    }
+
+    /// true if module (x, y) is dark, (0, 0) being the top left module
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.size()
+            && y < self.size()
+            && (self.rows[y * stride(self.size()) + x / 8] >> (7 - x % 8)) & 1 == 1
+    }
+
+    /// renders the symbol with `pixel` pixels per module and a `quiet_zone` modules wide border
+    #[cfg(any(feature = "render-png", feature = "render-jpeg"))]
+    pub fn gen_image(&self, pixel: u32, quiet_zone: u32) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        render(self.size(), self.size(), pixel, quiet_zone, |x, y| {
+            self.is_dark(x, y)
+        })
+    }
+
+    fn set(&mut self, x: usize, y: usize, flag: bool) {
+        let idx = y * stride(self.size()) + x / 8;
+        let byte = &mut self.rows[idx];
+        *byte = *byte & !(1 << (7 - x % 8)) | ((flag as u8) << (7 - x % 8));
+    }
+
+    /// the modules of the encoding region in the order the codeword bits are placed:
+    /// column pairs from the right, going up then down, the timing column having no pair
+    fn data_positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let size = self.size();
+        (2..size)
+            .rev()
+            .step_by(2)
+            .enumerate()
+            .flat_map(move |(pair, x)| {
+                (0..size)
+                    .map(move |idx| if pair % 2 == 0 { size - 1 - idx } else { idx })
+                    .flat_map(move |y| [(x, y), (x - 1, y)])
+            })
+            .filter(|&(x, y)| !is_function(x, y))
+    }
+
+    /// draws the finder and timing patterns and places the codeword bits
+    fn generate(&mut self, bits: impl Iterator<Item = bool>) {
+        for y in 0..7 {
+            for x in 0..7 {
+                let ring = x == 0 || x == 6 || y == 0 || y == 6;
+                let centre = (2..=4).contains(&x) && (2..=4).contains(&y);
+                self.set(x, y, ring || centre);
+            }
+        }
+
+        for idx in 8..self.size() {
+            self.set(idx, 0, idx % 2 == 0);
+            self.set(0, idx, idx % 2 == 0);
+        }
+
+        let positions = self.data_positions().collect::<Vec<_>>();
+        for ((x, y), bit) in positions.into_iter().zip(bits) {
+            self.set(x, y, bit);
+        }
+    }
+
+    //flips the encoding region where the mask is set, applying it twice undoes it
+    fn apply_mask(&mut self, mask: u8) {
+        let func = MASKS[MICRO_MASKS[mask as usize]];
+        let positions = self.data_positions().collect::<Vec<_>>();
+        for (x, y) in positions {
+            if func(y, x) {
+                let cell = self.is_dark(x, y);
+                self.set(x, y, !cell);
+            }
+        }
+    }
+
+    /// dark modules along the right and bottom edges (timing patterns excluded), the higher the better
+    fn score(&self) -> usize {
+        let last = self.size() - 1;
+        let right = (1..=last).filter(|&y| self.is_dark(last, y)).count();
+        let bottom = (1..=last).filter(|&x| self.is_dark(x, last)).count();
+        16 * right.min(bottom) + right.max(bottom)
+    }
+
+    fn add_format_symbols(&mut self) {
+        let symbol = match (self.version, self.ecc) {
+            (1, _) | (_, None) => 0,
+            (version, Some(ECCLevel::Low)) => 2 * version - 3,
+            (version, Some(ECCLevel::Medium)) => 2 * version - 2,
+            _ => 7,
+        };
+
+        let data = (symbol << 2) | self.mask;
+        let fmt = (((data as u16) << 10) | get_format_ecc(data)) ^ 0b100010001000101;
+
+        //down the column right of the finder, then leftwards along the row below it
+        for idx in 0..15 {
+            let (x, y) = if idx < 8 { (8, idx + 1) } else { (15 - idx, 8) };
+            self.set(x, y, (fmt >> idx) & 1 == 1);
+        }
+    }
+
+    /// masks the symbol and writes the format information
+    /// `forced` pins the mask pattern, otherwise the one with the highest score is chosen
+    fn choose_mask(&mut self, forced: Option<u8>) {
+        let mut max_score = 0;
+        let mut best = 0;
+
+        for mask_idx in 0..MICRO_MASKS.len() as u8 {
+            if forced.is_some_and(|mask| mask != mask_idx) {
+                continue;
+            }
+
+            self.apply_mask(mask_idx);
+            let score = self.score();
+            if forced.is_some() || score > max_score {
+                max_score = score;
+                best = mask_idx;
+            }
+
+            self.apply_mask(mask_idx);
+        }
+
+        self.mask = best;
+        self.apply_mask(best);
+        self.add_format_symbols();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::QrOptions;
+
+    fn options(ecc: ECCLevel, mask: Option<u8>) -> QrOptions {
+        let mut builder = QrOptions::builder();
+        builder.ecc(ecc);
+        if let Some(mask) = mask {
+            builder.mask(mask);
+        }
+
+        builder.build().unwrap()
+    }
+
+    fn encode(text: &str, ecc: ECCLevel) -> Result<MicroQRCode, QrError> {
+        Generator::new(String::from(text), options(ecc, None)).run_micro()
+    }
+
+    //the 15 format bits, the least significant one right of the finder's top row
+    fn format_bits(symbol: &MicroQRCode) -> u16 {
+        (0..15)
+            .filter(|&idx| {
+                let (x, y) = if idx < 8 { (8, idx + 1) } else { (15 - idx, 8) };
+                symbol.is_dark(x, y)
+            })
+            .fold(0, |fmt, idx| fmt | 1 << idx)
+    }
+
+    //BCH(15, 5) with the generator x^10 + x^8 + x^5 + x^4 + x^2 + x + 1, masked with 0x4445
+    fn expected_format(symbol_number: u16, mask: u16) -> u16 {
+        let data = (symbol_number << 2) | mask;
+        let mut rem = data << 10;
+        for bit in (10..15).rev() {
+            if rem >> bit & 1 == 1 {
+                rem ^= 0x537 << (bit - 10);
+            }
+        }
+
+        ((data << 10) | rem) ^ 0x4445
+    }
+
+    //ISO/IEC 18004 Annex I: "01234567" in M2-L
+    #[test]
+    fn iso_sample_m2_l() {
+        let symbol = encode("01234567", ECCLevel::Low).unwrap();
+        assert_eq!(symbol.version(), 2);
+        assert_eq!(symbol.ecc_level(), Some(ECCLevel::Low));
+        assert_eq!(symbol.mask(), 1);
+
+        //5 data codewords then 5 error correction codewords, read back in placement order
+        let func = MASKS[MICRO_MASKS[symbol.mask() as usize]];
+        let bits = symbol
+            .data_positions()
+            .map(|(x, y)| symbol.is_dark(x, y) != func(y, x))
+            .collect::<Vec<_>>();
+        let codewords = bits
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0u8, |acc, &bit| (acc << 1) | bit as u8))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            codewords,
+            [0x40, 0x18, 0xAC, 0xC3, 0x00, 0x86, 0x0D, 0x22, 0xAE, 0x30]
+        );
+
+        let expected = [
+            "#######.#.#.#",
+            "#.....#.###.#",
+            "#.###.#..##.#",
+            "#.###.#..####",
+            "#.###.#.###..",
+            "#.....#.#...#",
+            "#######..####",
+            ".........##..",
+            "##.#....#...#",
+            ".##.#.#.#.#.#",
+            "###..#######.",
+            "...#.#....##.",
+            "###.#..##.###",
+        ];
+        for (y, row) in expected.iter().enumerate() {
+            let read = (0..13)
+                .map(|x| if symbol.is_dark(x, y) { '#' } else { '.' })
+                .collect::<String>();
+            assert_eq!(read, *row, "row {}", y);
+        }
+    }
+
+    #[test]
+    fn format_information() {
+        //the symbol numbers of ISO/IEC 18004, M1 = 0 to M4-Q = 7
+        let symbols = [
+            ("12345", ECCLevel::Low, 0),
+            ("1234567890", ECCLevel::Low, 1),
+            ("12345678", ECCLevel::Medium, 2),
+            ("HELLO WORLD", ECCLevel::Low, 3),
+            ("HELLO WORLD", ECCLevel::Medium, 4),
+            ("HELLO MICRO QR CODE", ECCLevel::Low, 5),
+            ("HELLO MICRO QR", ECCLevel::Medium, 6),
+            ("HELLO MICRO", ECCLevel::Quartile, 7),
+        ];
+        for (text, ecc, number) in symbols {
+            for mask in 0..4 {
+                let symbol = Generator::new(String::from(text), options(ecc, Some(mask)))
+                    .run_micro()
+                    .unwrap();
+                assert_eq!(symbol.mask(), mask);
+                assert_eq!(
+                    format_bits(&symbol),
+                    expected_format(number, mask as u16),
+                    "{} at {:?}, mask {}",
+                    text,
+                    ecc,
+                    mask
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn capacity_limits() {
+        //the most numeric, alphanumeric and byte characters each symbol holds
+        //M1 is only used at level L
+        let limits = [
+            (ECCLevel::Low, 1, 5, 0, 0),
+            (ECCLevel::Low, 2, 10, 6, 0),
+            (ECCLevel::Low, 3, 23, 14, 9),
+            (ECCLevel::Low, 4, 35, 21, 15),
+            (ECCLevel::Medium, 2, 8, 5, 0),
+            (ECCLevel::Medium, 3, 18, 11, 7),
+            (ECCLevel::Medium, 4, 30, 18, 13),
+        ];
+        for (ecc, version, numeric, alphanumeric, byte) in limits {
+            for (ch, len) in [('1', numeric), ('A', alphanumeric), ('a', byte)] {
+                if len == 0 {
+                    continue;
+                }
+
+                let text = ch.to_string().repeat(len);
+                assert_eq!(encode(&text, ecc).unwrap().version(), version, "{}", text);
+                let longer = ch.to_string().repeat(len + 1);
+                assert!(encode(&longer, ecc).map_or(true, |symbol| symbol.version() > version));
+            }
+        }
+
+        assert_eq!(
+            encode(&"1".repeat(21), ECCLevel::Quartile)
+                .unwrap()
+                .version(),
+            4
+        );
+        assert!(encode(&"1".repeat(22), ECCLevel::Quartile).is_err());
+        assert!(encode(&"1".repeat(36), ECCLevel::Low).is_err());
+        assert!(encode("1", ECCLevel::High).is_err());
+    }
+}
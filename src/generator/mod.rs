@@ -1,18 +1,82 @@
 mod bitstream;
+#[cfg(feature = "std")]
+mod charset;
+mod codewords;
 mod data;
 mod ecc;
 mod encoder;
+#[cfg(feature = "std")]
+mod export;
+mod fixed;
+#[cfg(feature = "std")]
+mod gs1;
+#[cfg(feature = "std")]
+mod input;
+mod matrix;
+#[cfg(feature = "std")]
+mod micro;
+#[cfg(feature = "std")]
+mod options;
+#[cfg(feature = "std")]
+mod preflight;
+#[cfg(feature = "std")]
 mod qr;
+#[cfg(feature = "scanner")]
+mod reader;
+#[cfg(feature = "std")]
+mod rmqr;
+mod segment;
+#[cfg(feature = "std")]
+mod url;
+#[cfg(feature = "std")]
 use bitstream::BitStream;
-use data::{qr_capacity_query, qr_version_query, BlockDivision};
-use ecc::ErrorCorrection;
+#[cfg(feature = "std")]
+use charset::eci_bits;
+#[cfg(feature = "std")]
+pub use charset::Charset;
+#[cfg(feature = "std")]
+use codewords::{finish, interleaved, total_codewords};
+#[cfg(feature = "std")]
+use data::qr_capacity_query;
+#[cfg(feature = "std")]
 use encoder::{
-    alphanum_value, is_kanji, AlphanumEncoder, BytesEncoder, Encoder, KanjiEncoder, NumeralEncoder,
+    encode_eci, encode_fnc1, encode_structured_append, is_hanzi, is_kanji, STRUCTURED_APPEND_BITS,
 };
-use qr::QRCode;
-use std::{fs::File, io::Read, process::exit};
+pub use fixed::{codeword_buffer_len, encode_into, matrix_buffer_len, QrMatrix};
+#[cfg(feature = "std")]
+use gs1::GS;
+#[cfg(feature = "std")]
+pub use gs1::{DigitalLink, ElementString, Fnc1};
+#[cfg(feature = "std")]
+use input::has_bom;
+#[cfg(feature = "std")]
+pub use input::{decode_base64, decode_hex, decode_input};
+pub use matrix::{Module, ModuleKind, Penalty};
+#[cfg(feature = "std")]
+pub use micro::MicroQRCode;
+#[cfg(feature = "std")]
+pub use options::{QrOptions, QrOptionsBuilder};
+#[cfg(feature = "std")]
+pub use preflight::Preflight;
+#[cfg(feature = "std")]
+pub use qr::QRCode;
+#[cfg(feature = "scanner")]
+pub(crate) use reader::symbol_text;
+#[cfg(feature = "std")]
+pub use rmqr::RMQRCode;
+pub use segment::Mode;
+#[cfg(feature = "std")]
+pub use segment::Segment;
+#[cfg(feature = "std")]
+pub use url::compact_url;
 
-#[derive(Debug)]
+#[cfg(feature = "std")]
+use core::ops::{Range, RangeInclusive};
+
+#[cfg(feature = "std")]
+use crate::error::QrError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ECCLevel {
     Low,
     Medium,
@@ -20,409 +84,1023 @@ pub enum ECCLevel {
     High,
 }
 
+impl ECCLevel {
+    /// every level, from the least to the most redundant
+    pub const ALL: [ECCLevel; 4] = [
+        ECCLevel::Low,
+        ECCLevel::Medium,
+        ECCLevel::Quartile,
+        ECCLevel::High,
+    ];
+}
+
+#[cfg(feature = "std")]
 #[derive(Debug)]
-pub struct Flag {
-    pub data: bool,
-    pub bytes: bool,
-    pub min_vers: u8,
-    pub ecc: ECCLevel,
+enum Payload {
+    Text(String),
+    //never segmented, always written as a single byte segment
+    Binary(Vec<u8>),
+    //octets already in the charset the options pin, written as they are under its ECI
+    Encoded(Vec<u8>),
+    //segmented by the caller
+    Segments(Vec<Segment>),
 }
 
-impl Flag {
-    pub fn new() -> Self {
-        Self {
-            data: false,
-            bytes: false,
-            min_vers: 1,
-            ecc: ECCLevel::Quartile,
+#[cfg(feature = "std")]
+impl Payload {
+    //number of characters (octets for binary data), the units it is split at
+    fn len(&self) -> usize {
+        match self {
+            Payload::Text(text) => text.chars().count(),
+            Payload::Binary(data) | Payload::Encoded(data) => data.len(),
+            Payload::Segments(segments) => segments.iter().map(Segment::len).sum(),
+        }
+    }
+
+    //the characters in `range`, segments keep their modes
+    fn slice(&self, range: Range<usize>) -> Payload {
+        match self {
+            Payload::Text(text) => {
+                Payload::Text(text.chars().skip(range.start).take(range.len()).collect())
+            }
+            Payload::Binary(data) => Payload::Binary(data[range].to_vec()),
+            Payload::Encoded(data) => Payload::Encoded(data[range].to_vec()),
+            Payload::Segments(segments) => {
+                let mut start = 0;
+                let mut sliced = Vec::new();
+                for seg in segments {
+                    let end = start + seg.len();
+                    if start < range.end && range.start < end {
+                        let from = range.start.max(start) - start;
+                        let to = range.end.min(end) - start;
+                        sliced.push(seg.slice(from..to));
+                    }
+                    start = end;
+                }
+
+                Payload::Segments(sliced)
+            }
         }
     }
 }
 
+//where a symbol stands in a structured append sequence
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+struct Sequence {
+    index: u8,
+    total: u8,
+    parity: u8,
+}
+
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub struct Generator {
-    text: String,
-    output: String,
-    size: u32,
-    flag: Flag,
+    payload: Payload,
+    options: QrOptions,
+    sequence: Option<Sequence>,
 }
 
+#[cfg(feature = "std")]
 impl Generator {
-    pub fn new(data: String, output: String, size: u32, flag: Flag) -> Self {
-        let text = if flag.data {
-            match File::open(data) {
-                Ok(mut file) => {
-                    let mut buf = String::new();
-                    match file.read_to_string(&mut buf) {
-                        Ok(_) => buf,
-                        Err(e) => {
-                            println!("Error while reading file. {}", e);
-                            exit(0);
-                        }
-                    }
-                }
-                Err(e) => {
-                    println!("Error while reading file. {}", e);
-                    exit(0);
-                }
-            }
-        } else {
-            data
-        };
+    pub fn new(text: String, options: QrOptions) -> Self {
+        Self {
+            payload: Payload::Text(text),
+            options,
+            sequence: None,
+        }
+    }
 
+    /// the octets are encoded as they are in byte mode, whether or not they are valid UTF-8
+    pub fn from_bytes(data: Vec<u8>, options: QrOptions) -> Self {
         Self {
-            text,
-            output,
-            size,
-            flag,
+            payload: Payload::Binary(data),
+            options,
+            sequence: None,
         }
     }
 
-    fn get_data_size(mode: u8, len: usize, str: &str) -> u16 {
-        match mode {
-            0 => {
-                (10 * (len / 3)
-                    + (if len % 3 == 1 {
-                        4
-                    } else if len % 3 == 2 {
-                        7
-                    } else {
-                        0
-                    })) as u16
-            }
-            1 => (11 * (len / 2) + (if len % 2 == 1 { 6 } else { 0 })) as u16,
-            2 => (8 * str.len()) as u16,
-            3 => (13 * len) as u16,
-            4 => 0,
-            _ => {
-                eprintln!("Something went wrong during qr code generation: weird mode {mode}");
-                exit(0);
-            }
+    /// the text of a data file written in `encoding` (see `decode_input`)
+    ///
+    /// when the options pin `encoding` as the charset and there is no byte order mark, the octets are
+    /// written as they are: through the text if it encodes back to the same octets, so that kanji and
+    /// the other modes can still be used, otherwise as a single byte segment under the charset's ECI
+    pub fn from_encoded(
+        data: Vec<u8>,
+        encoding: Charset,
+        options: QrOptions,
+    ) -> Result<Self, QrError> {
+        let text = decode_input(&data, encoding)?;
+        if options.charset() == Some(encoding)
+            && !has_bom(&data)
+            && encoding.encode(&text).ok().as_ref() != Some(&data)
+        {
+            return Ok(Self {
+                payload: Payload::Encoded(data),
+                options,
+                sequence: None,
+            });
         }
+
+        Ok(Self::new(text, options))
     }
 
-    fn get_version(&self) -> (u8, Vec<(u16, u8)>) {
-        if self.flag.bytes {
-            let length = self.text.len();
-            let v1 = qr_version_query(&self.flag.ecc, length * 8 + 12); //test for v1-10 by 8 bit char count indicator
-            let v2 = qr_version_query(&self.flag.ecc, length * 8 + 20); //test for v11-40 by 16 bit char count indicator
+    /// the segments are encoded in the given order and modes, the generator only picks the version
+    pub fn from_segments(segments: Vec<Segment>, options: QrOptions) -> Self {
+        Self {
+            payload: Payload::Segments(segments),
+            options,
+            sequence: None,
+        }
+    }
 
-            (self.flag.min_vers.max(v1.min(v2)), Vec::new())
-        } else {
-            let mut iter = self.text.chars().rev().peekable();
-            if let None = iter.peek() {
-                return (0, Vec::new());
-            }
+    /// the text to segment, `None` if the payload is already segmented
+    fn text(&self) -> Option<&str> {
+        match &self.payload {
+            Payload::Text(text) if !self.options.force_bytes() => Some(text),
+            _ => None,
+        }
+    }
 
-            let len = iter.clone().count();
-            let mut dp = vec![vec![u16::MAX as usize; 4]; len + 1];
-            let mut next = vec![vec![None; 4]; len + 1];
+    /// the charset picked in the options, otherwise the one `Charset::of` picks
+    fn charset(&self, segments: &[Segment]) -> Charset {
+        self.options
+            .charset()
+            .unwrap_or_else(|| Charset::of(segments))
+    }
 
-            for mode in 0..=3 {
-                dp[len][mode] = 0;
-            }
+    /// size of the segments in `version`, the structured append, ECI and FNC1 headers included
+    fn bits(&self, segments: &[Segment], version: u8) -> usize {
+        let charset = self.charset(segments);
+        let sequence = self.sequence.map_or(0, |_| STRUCTURED_APPEND_BITS);
+        let eci = self.eci(segments).map_or(0, eci_bits);
+        let fnc1 = self.options.fnc1().map_or(0, Fnc1::bits);
+        sequence
+            + eci
+            + fnc1
+            + segments
+                .iter()
+                .map(|seg| seg.bits(version, charset))
+                .sum::<usize>()
+    }
 
-            let mut buffer = String::new();
-
-            for idx in (0..len).rev() {
-                buffer.push(iter.next().unwrap());
-                for mode in 0usize..=3 {
-                    let mut max_size = 0;
-                    let mut check_iter = buffer.chars().rev();
-                    let mut str = String::new();
-                    while let Some(ch) = check_iter.next() {
-                        if match mode {
-                            0 => ch.is_numeric(),
-                            1 => alphanum_value(ch).is_some(),
-                            2 => true,
-                            3 => is_kanji(ch),
-                            _ => false,
-                        } {
-                            str.push(ch);
-                            max_size += 1;
-                        } else {
-                            break;
-                        }
-                    }
+    /// the ECI written at the start of the symbol, see `Charset::header`
+    fn eci(&self, segments: &[Segment]) -> Option<u32> {
+        let charset = self.charset(segments);
+        match self.payload {
+            Payload::Encoded(_) => (charset != Charset::Latin1).then(|| charset.eci()),
+            _ => charset.header(segments),
+        }
+    }
 
-                    if str.is_empty() {
-                        continue;
+    /// true if `ch` can leave byte mode for kanji mode, or hanzi mode if the options allow it
+    fn is_double_byte(&self, ch: char) -> bool {
+        is_kanji(ch) || (self.options.hanzi() && is_hanzi(ch))
+    }
+
+    /// the charset of the whole payload, the one `get_version` picks for text
+    fn payload_charset(&self) -> Charset {
+        self.options
+            .charset()
+            .unwrap_or_else(|| match (&self.payload, self.text()) {
+                //kanji and hanzi can leave byte mode, anything else outside of Latin-1 needs UTF-8
+                (_, Some(text)) => {
+                    if text
+                        .chars()
+                        .all(|ch| Charset::Latin1.accepts(ch) || self.is_double_byte(ch))
+                    {
+                        Charset::Latin1
+                    } else {
+                        Charset::Utf8
                     }
+                }
+                (Payload::Text(text), None) => Charset::of(&[Segment::bytes(text)]),
+                (Payload::Segments(segments), None) => Charset::of(segments),
+                (Payload::Binary(_) | Payload::Encoded(_), None) => Charset::Latin1,
+            })
+    }
+
+    /// the smallest version in `versions` that holds all the segments, 41 if there is none
+    fn fit_version(&self, segments: &[Segment], versions: RangeInclusive<u8>) -> u8 {
+        if segments.iter().all(|seg| seg.data().is_empty()) {
+            return 0;
+        }
 
-                    let mut str_iter = str.chars();
-                    let mut str_div = String::new();
-
-                    for size in 1..=max_size {
-                        str_div.push(str_iter.next().unwrap());
-                        let cost = Self::get_data_size(mode as u8, size, &str_div);
-                        for next_mode in 0usize..=3 {
-                            let mode_indicator_size = if mode == next_mode { 0 } else { 4 };
-                            let total_size =
-                                ((mode_indicator_size + cost) as usize) + dp[idx + size][next_mode];
-
-                            if total_size < dp[idx][mode] {
-                                dp[idx][mode] = total_size;
-                                next[idx][mode] = Some((idx + size, next_mode));
-                            }
-                        }
+        versions
+            .into_iter()
+            .find(|&version| {
+                self.bits(segments, version) <= qr_capacity_query(&self.options.ecc(), version)
+            })
+            .unwrap_or(41)
+    }
+
+    /// the header and count indicator sizes of each mode in QR code `version`,
+    /// hanzi mode being left out unless the options allow it
+    fn qr_headers(&self, version: u8) -> [Option<(usize, usize)>; 5] {
+        Mode::ALL.map(|mode| {
+            (mode != Mode::Hanzi || self.options.hanzi())
+                .then(|| (mode.header_bits(version), mode.count_bits(version)))
+        })
+    }
+
+    /// the segments of `text` taking the fewest bits
+    ///
+    /// `octets` is the length of each character in the byte mode charset, `None` if it cannot be represented.
+    /// `headers` is the size of the whole header and of the count indicator of each mode in `Mode::ALL` order,
+    /// `None` for the modes the symbol does not use. In FNC1 mode (`fnc1`) alphanumeric segments
+    /// write `GS` as `%` and `%` as `%%`.
+    fn segment(
+        text: &str,
+        octets: &[Option<usize>],
+        headers: [Option<(usize, usize)>; 5],
+        fnc1: bool,
+    ) -> Result<Vec<Segment>, QrError> {
+        //sizes are in sixths of a bit so that a digit (10 bits per 3) and an alphanumeric
+        //character (11 bits per 2) cost a whole number, the size of a segment is rounded up when it ends
+        let header = headers.map(|header| header.map_or(0, |(bits, _)| 6 * bits));
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut dp = vec![[usize::MAX; Mode::ALL.len()]; chars.len()];
+        let mut prev = vec![[0; Mode::ALL.len()]; chars.len()];
+
+        for (idx, &ch) in chars.iter().enumerate() {
+            for (mode, &accepted) in Mode::ALL.iter().enumerate() {
+                if headers[mode].is_none() {
+                    continue;
+                }
+
+                //6 characters take `data_bits(6)` bits, so one takes that many sixths
+                let cost = match accepted {
+                    Mode::Byte => match octets[idx] {
+                        Some(len) => len * accepted.data_bits(6),
+                        None => continue,
+                    },
+                    Mode::Alphanumeric if fnc1 && ch == '%' => 2 * accepted.data_bits(6),
+                    Mode::Alphanumeric if fnc1 && ch == GS => accepted.data_bits(6),
+                    _ if accepted.accepts(ch) => accepted.data_bits(6),
+                    _ => continue,
+                };
+
+                if idx == 0 {
+                    dp[idx][mode] = header[mode] + cost;
+                    continue;
+                }
+
+                for prev_mode in 0..Mode::ALL.len() {
+                    let size = match dp[idx - 1][prev_mode] {
+                        usize::MAX => continue,
+                        size if prev_mode == mode => size,
+                        size => 6 * size.div_ceil(6) + header[mode],
+                    };
+
+                    if size + cost < dp[idx][mode] {
+                        dp[idx][mode] = size + cost;
+                        prev[idx][mode] = prev_mode;
                     }
                 }
             }
+        }
+
+        //the mode of every character, from the last one back
+        let mut modes = vec![0; chars.len()];
+        let mut mode = (0..Mode::ALL.len())
+            .min_by_key(|&mode| dp[chars.len() - 1][mode].div_ceil(6))
+            .unwrap();
+        for idx in (0..chars.len()).rev() {
+            modes[idx] = mode;
+            mode = prev[idx][mode];
+        }
+
+        let segment = |mode: Mode, chars: &[char]| {
+            let str = chars.iter().collect::<String>();
+            match mode {
+                Mode::Alphanumeric if fnc1 => Segment::new(mode, &gs1::escape(&str)),
+                _ => Segment::new(mode, &str),
+            }
+        };
+
+        //a segment longer than its count indicator can tell is split
+        let mut segments = Vec::new();
+        let mut start = 0;
+        let mut count = 0;
+        for idx in 0..chars.len() {
+            let accepted = Mode::ALL[modes[idx]];
+            let count_bits = headers[modes[idx]].map_or(0, |(_, bits)| bits);
+            let len = match accepted {
+                Mode::Byte => octets[idx].unwrap_or(0),
+                Mode::Alphanumeric if fnc1 && chars[idx] == '%' => 2,
+                _ => 1,
+            };
+
+            if idx > start && (modes[idx] != modes[start] || count + len >= 1 << count_bits) {
+                segments.push(segment(Mode::ALL[modes[start]], &chars[start..idx])?);
+                start = idx;
+                count = 0;
+            }
+            count += len;
+        }
+        segments.push(segment(Mode::ALL[modes[start]], &chars[start..])?);
+
+        Ok(segments)
+    }
 
-            let mut data = Vec::<(u16, u8)>::new();
-            let mut pos = 0;
-            let mut mode = (0..=3).min_by_key(|&x| dp[0][x]).unwrap();
+    /// the segments of `text`, whose characters the payload charset represents, in QR code `version`
+    fn text_segments(&self, text: &str, version: u8) -> Result<Vec<Segment>, QrError> {
+        //octets of each character in byte mode, `None` if the charset cannot represent it
+        let charset = self.payload_charset();
+        let octets = text
+            .chars()
+            .map(|ch| charset.char_len(ch))
+            .collect::<Vec<_>>();
 
-            while pos < len {
-                data.push((pos as u16, mode as u8));
-                if let Some((idx, next_mode)) = next[pos][mode] {
-                    pos = idx;
-                    mode = next_mode;
+        Self::segment(
+            text,
+            &octets,
+            self.qr_headers(version),
+            self.options.fnc1().is_some(),
+        )
+    }
+
+    /// how many characters of each mode (octets for byte mode), in `Mode::ALL` order, can be appended
+    /// to the payload and still fit the version and level of `preflight`
+    ///
+    /// text is segmented again with the characters appended, which may move them (or the end of the
+    /// text) to another mode. The segments of any other payload stay as they are, the characters
+    /// extend the last one or start a new one.
+    fn remaining(&self, preflight: &Preflight, segments: &[Segment]) -> [usize; 5] {
+        let (version, capacity) = (preflight.version(), preflight.capacity());
+        let charset = self.charset(segments);
+        let sample = |mode: Mode| match mode {
+            Mode::Numeric => Some('0'),
+            Mode::Alphanumeric => Some('A'),
+            Mode::Byte => Some('a'),
+            Mode::Kanji => Some('亜').filter(|&ch| is_kanji(ch)),
+            Mode::Hanzi => Some('啊').filter(|&ch| self.options.hanzi() && is_hanzi(ch)),
+        };
+
+        Mode::ALL.map(|mode| {
+            let Some(ch) = sample(mode) else {
+                return 0;
+            };
+
+            let Some(text) = self.text() else {
+                let (count, header) = match segments.last() {
+                    Some(last) if last.mode() == mode => (last.count(charset), 0),
+                    _ => (0, mode.header_bits(version)),
+                };
+
+                let free = preflight.remaining_bits();
+                let max_count = (1 << mode.count_bits(version)) - 1;
+                let used = mode.data_bits(count);
+                return (count..max_count)
+                    .take_while(|&count| header + mode.data_bits(count + 1) - used <= free)
+                    .count();
+            };
+
+            let fits = |count: usize| {
+                let mut extended = String::from(text);
+                extended.extend(core::iter::repeat_n(ch, count));
+                let generator = Generator {
+                    payload: Payload::Text(extended),
+                    options: self.options.clone(),
+                    sequence: self.sequence,
+                };
+                let text = generator.text().unwrap_or_default();
+                generator
+                    .text_segments(text, version)
+                    .is_ok_and(|segments| generator.bits(&segments, version) <= capacity)
+            };
+
+            //every character takes more than 3 bits, `capacity` of them never fit
+            let (mut low, mut high) = (0, capacity);
+            while low < high {
+                let mid = (low + high).div_ceil(2);
+                if fits(mid) {
+                    low = mid;
                 } else {
-                    break;
+                    high = mid - 1;
                 }
             }
 
-            let min_cost = dp[data[0].0 as usize][data[0].1 as usize] + 4;
-            let mut encoding = Vec::<(u16, u8)>::new();
-            let mut prev_mode = data[0].1;
-            let mut prev_pos = data[0].0;
-            for (pos, mode) in data {
-                if mode == prev_mode {
-                    continue;
+            low
+        })
+    }
+
+    /// the segments of a payload that is not segmented here
+    fn fixed_segments(&self) -> Vec<Segment> {
+        match &self.payload {
+            Payload::Text(text) => vec![Segment::bytes(text)],
+            Payload::Binary(data) | Payload::Encoded(data) => vec![Segment::binary(data)],
+            Payload::Segments(segments) => segments.clone(),
+        }
+    }
+
+    fn get_version(&self) -> Result<(u8, Vec<Segment>), QrError> {
+        let versions = self.options.min_version()..=self.options.max_version();
+        match self.text() {
+            None => {
+                let segments = self.fixed_segments();
+                let charset = self.charset(&segments);
+                for seg in &segments {
+                    if let (Mode::Byte, Some(text)) = (seg.mode(), seg.text()) {
+                        charset.check(text)?;
+                    }
                 }
 
-                encoding.push((pos - prev_pos, prev_mode));
-                prev_mode = mode;
-                prev_pos = pos;
+                Ok((self.fit_version(&segments, versions), segments))
             }
-            encoding.push((len as u16 - prev_pos, prev_mode));
-
-            let mut mode_count: Vec<usize> = vec![0, 0, 0, 0];
-            let mut chars_using_byte = String::new();
-            let mut text_iter = self.text.chars();
-            for (len, mode) in &encoding {
-                mode_count[*mode as usize] += 1;
-                for _ in 0..*len {
-                    if *mode == 2 {
-                        chars_using_byte.push(text_iter.next().unwrap());
+            Some(text) => {
+                if text.is_empty() {
+                    return Ok((0, Vec::new()));
+                }
+
+                let charset = self.payload_charset();
+                if let Some(ch) = text
+                    .chars()
+                    .find(|&ch| !charset.accepts(ch) && !self.is_double_byte(ch))
+                {
+                    return Err(QrError::UnrepresentableCharacter { ch, charset });
+                }
+
+                //the count indicators grow at versions 10 and 27, which can change the best segmentation,
+                //so each class is segmented on its own and the first one holding the text wins
+                let mut segments = Vec::new();
+                for class in [1..=9, 10..=26, 27..=40] {
+                    let first = *class.start().max(versions.start());
+                    let last = *class.end().min(versions.end());
+                    if first > last {
+                        continue;
+                    }
+
+                    segments = self.text_segments(text, last)?;
+                    let version = self.fit_version(&segments, first..=last);
+                    if version <= last {
+                        return Ok((version, segments));
                     }
                 }
+
+                Ok((41, segments))
             }
-            
-            let v0 = qr_version_query(
-                &self.flag.ecc,
-                min_cost
-                    + mode_count[0] * 14
-                    + mode_count[1] * 13
-                    + mode_count[2] * 16
-                    + mode_count[3] * 12,
-            );
-            (
-                self.flag.min_vers.max(if v0 <= 26 {
-                    let v1 = qr_version_query(
-                        &self.flag.ecc,
-                        min_cost
-                            + mode_count[0] * 12
-                            + mode_count[1] * 11
-                            + mode_count[2] * 16
-                            + mode_count[3] * 10,
-                    );
-                    if v1 <= 9 {
-                        qr_version_query(
-                            &self.flag.ecc,
-                            min_cost
-                                + mode_count[0] * 10
-                                + mode_count[1] * 9
-                                + mode_count[2] * 8
-                                + mode_count[3] * 8,
-                        )
-                    } else {
-                        v1
-                    }
+        }
+    }
+
+    /// the segments to encode and the exact size they take
+    fn plan(&self) -> Result<(Preflight, Vec<Segment>), QrError> {
+        let (version, segments) = self.get_version()?;
+        if version == 0 {
+            return Err(QrError::EmptyInput);
+        } else if version > self.options.max_version() {
+            //not even the largest symbol at the lowest level holds it
+            return Err(
+                if self.bits(&segments, 40) > qr_capacity_query(&ECCLevel::Low, 40) {
+                    QrError::InputTooLong
                 } else {
-                    v0
-                }) as u8,
-                encoding
-            )
+                    QrError::NoVersionFits
+                },
+            );
         }
+
+        let bits = self.bits(&segments, version);
+
+        //the version stays, the level is raised as far as the data still fits it
+        let ecc = if self.options.boost_ecc() {
+            ECCLevel::ALL
+                .into_iter()
+                .rev()
+                .take_while(|&ecc| ecc != self.options.ecc())
+                .find(|ecc| bits <= qr_capacity_query(ecc, version))
+                .unwrap_or(self.options.ecc())
+        } else {
+            self.options.ecc()
+        };
+
+        Ok((Preflight::new(version, ecc, bits), segments))
+    }
+
+    /// the version the input gets, the level it is made at and the exact number of bits it takes,
+    /// without encoding it
+    pub fn preflight(&self) -> Result<Preflight, QrError> {
+        let (preflight, segments) = self.plan()?;
+        let remaining = self.remaining(&preflight, &segments);
+        Ok(preflight.with_remaining(remaining))
     }
 
-    fn combine_data_err(data: Vec<(usize, Vec<u8>)>, err: Vec<Vec<u8>>) -> Vec<u8> {
-        let mut res = Vec::new();
+    /// encodes the text and returns the finished (masked) symbol
+    pub fn run(self) -> Result<QRCode, QrError> {
+        let (preflight, segments) = self.plan()?;
+        let version = preflight.version();
+
+        let ecc = preflight.ecc_level();
+        let capacity = preflight.capacity();
+        let mut codewords = vec![0u8; total_codewords(version)];
+        let mut stream = BitStream::new(&mut codewords[..capacity / 8]);
+
+        if let Some(Sequence {
+            index,
+            total,
+            parity,
+        }) = self.sequence
+        {
+            encode_structured_append(index, total, parity, &mut stream);
+        }
+        let charset = self.charset(&segments);
+        if let Some(designator) = self.eci(&segments) {
+            encode_eci(designator, &mut stream);
+        }
+        if let Some(fnc1) = self.options.fnc1() {
+            encode_fnc1(fnc1, &mut stream);
+        }
+        for segment in &segments {
+            segment.encode(&mut stream, version, charset)?;
+        }
+
+        //the blocks cannot take more, a symbol is never made of a truncated stream
+        if stream.overflowed() {
+            return Err(QrError::CapacityExceeded {
+                bits: stream.size(),
+                capacity,
+            });
+        }
+
+        if stream.size() <= capacity - 4 {
+            stream.push_bits(0, 4);
+        }
+
+        let used = stream.len();
+        finish(&mut codewords, used, version, &ecc);
 
-        let mut max_len = 0;
-        for (_, vec) in &data {
-            max_len = max_len.max(vec.len());
+        Ok(QRCode::new(
+            interleaved(&codewords, version, &ecc).collect(),
+            version,
+            &ecc,
+            self.options.mask(),
+        )
+        .with_segments(segments))
+    }
+
+    /// encodes the payload into a single symbol if it fits one, otherwise splits it into a structured
+    /// append sequence of up to 16 symbols that scanners put back together, each of the smallest
+    /// version holding its part
+    ///
+    /// The bundled `Scanner` cannot read the symbols of a sequence, its decoder does not know the
+    /// structured append mode.
+    pub fn run_structured(self) -> Result<Vec<QRCode>, QrError> {
+        match self.plan() {
+            Ok(_) => return Ok(vec![self.run()?]),
+            Err(QrError::NoVersionFits | QrError::InputTooLong) => {}
+            Err(e) => return Err(e),
         }
 
-        let mut idx = 0;
-        while idx < max_len {
-            for (_, code) in &data {
-                if idx < code.len() {
-                    res.push(code[idx]);
+        //every part is written in the charset of the whole payload
+        let options = self.options.with_charset(self.payload_charset());
+        let part = |range: Range<usize>| Generator {
+            payload: self.payload.slice(range),
+            options: options.clone(),
+            sequence: Some(Sequence {
+                index: 0,
+                total: 16,
+                parity: 0,
+            }),
+        };
+        let fits = |range: Range<usize>| match part(range).plan() {
+            Ok(_) => Ok(true),
+            Err(QrError::NoVersionFits | QrError::InputTooLong) => Ok(false),
+            Err(e) => Err(e),
+        };
+
+        //each part is the longest that fits from where the previous one ends
+        let len = self.payload.len();
+        let mut parts = Vec::new();
+        let mut start = 0;
+        while start < len {
+            if parts.len() == 16 || !fits(start..start + 1)? {
+                return Err(QrError::InputTooLong);
+            }
+
+            let (mut low, mut high) = (start + 1, len);
+            while low < high {
+                let mid = (low + high).div_ceil(2);
+                if fits(start..mid)? {
+                    low = mid;
+                } else {
+                    high = mid - 1;
                 }
             }
-            idx += 1;
+
+            parts.push(part(start..low));
+            start = low;
         }
 
-        max_len = 0;
-        for vec in &err {
-            max_len = max_len.max(vec.len());
+        let mut parity = 0;
+        for generator in &parts {
+            let (_, segments) = generator.get_version()?;
+            let charset = generator.charset(&segments);
+            for seg in &segments {
+                parity ^= seg.parity(charset)?;
+            }
         }
 
-        idx = 0;
-        while idx < max_len {
-            for code in &err {
-                if idx < code.len() {
-                    res.push(code[idx]);
-                }
+        let total = parts.len() as u8;
+        parts
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut generator)| {
+                generator.sequence = Some(Sequence {
+                    index: index as u8,
+                    total,
+                    parity,
+                });
+                generator.run()
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use codewords::interleaved_order;
+    use matrix::{Layout, MASKS};
+
+    fn options(version: u8) -> QrOptions {
+        let mut builder = QrOptions::builder();
+        builder.ecc(ECCLevel::Low).version(version);
+        builder.build().unwrap()
+    }
+
+    //the data codewords of a symbol, unmasked and back in block order
+    fn data_codewords(symbol: &QRCode) -> Vec<u8> {
+        let (version, ecc) = (symbol.version(), symbol.ecc_level());
+        let total = total_codewords(version);
+        let mask = MASKS[symbol.mask() as usize];
+
+        let mut placed = vec![0u8; total];
+        let positions = Layout::new(version).data_positions().take(8 * total);
+        for (idx, (x, y)) in positions.enumerate() {
+            if symbol.is_dark(x, y) != mask(y, x) {
+                placed[idx / 8] |= 0x80 >> (idx % 8);
+            }
+        }
+
+        let mut codewords = vec![0u8; total];
+        for (idx, src) in interleaved_order(version, &ecc).enumerate() {
+            codewords[src] = placed[idx];
+        }
+        codewords.truncate(qr_capacity_query(&ecc, version) / 8);
+        codewords
+    }
+
+    //the mode indicator, index, total and parity of the structured append header
+    fn header(symbol: &QRCode) -> (u8, u8, u8, u8) {
+        let codewords = data_codewords(symbol);
+        (
+            codewords[0] >> 4,
+            codewords[0] & 0xF,
+            (codewords[1] >> 4) + 1,
+            (codewords[1] << 4) | (codewords[2] >> 4),
+        )
+    }
+
+    fn text(symbol: &QRCode) -> String {
+        symbol.segments().iter().filter_map(Segment::text).collect()
+    }
+
+    #[test]
+    fn structured_append_parts() {
+        //version 1-L holds 152 bits: 20 of header, 14 of numeric header and 35 digits in 117
+        let digits = (0..100)
+            .map(|idx| char::from(b'0' + idx % 7))
+            .collect::<String>();
+        let parity = digits.bytes().fold(0, |parity, byte| parity ^ byte);
+
+        let parts = Generator::new(digits.clone(), options(1))
+            .run_structured()
+            .unwrap();
+        let lens = parts
+            .iter()
+            .map(|part| text(part).len())
+            .collect::<Vec<_>>();
+        assert_eq!(lens, [35, 35, 30]);
+        assert_eq!(parts.iter().map(text).collect::<String>(), digits);
+
+        for (index, part) in parts.iter().enumerate() {
+            assert_eq!(header(part), (0b0011, index as u8, 3, parity));
+        }
+    }
+
+    #[test]
+    fn structured_append_parity() {
+        //the XOR of the octets of the whole payload in its charset, Latin-1 then UTF-8
+        for (text, latin1) in [("Ünïcödé ", true), ("€uro ", false)] {
+            let text = text.repeat(8);
+            let octets = if latin1 {
+                text.chars().map(|ch| ch as u8).collect()
+            } else {
+                text.clone().into_bytes()
+            };
+            let parity = octets.iter().fold(0, |parity, byte| parity ^ byte);
+            let parts = Generator::new(text, options(2)).run_structured().unwrap();
+            assert!(parts.len() > 1);
+            for part in &parts {
+                assert_eq!(header(part).3, parity);
             }
-            idx += 1;
         }
+    }
 
-        res
+    #[test]
+    fn structured_append_limits() {
+        //a payload that fits one symbol has no header
+        let single = Generator::new(String::from("0123"), options(1))
+            .run_structured()
+            .unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(header(&single[0]).0, 0b0001);
+
+        //16 symbols of 35 digits at most
+        let parts = Generator::new("7".repeat(560), options(1))
+            .run_structured()
+            .unwrap();
+        assert_eq!(parts.len(), 16);
+        assert_eq!(header(&parts[15]).1, 15);
+        assert_eq!(header(&parts[15]).2, 16);
+
+        let result = Generator::new("7".repeat(561), options(1)).run_structured();
+        assert!(matches!(result, Err(QrError::InputTooLong)));
     }
 
-    pub fn run(self) {
-        if self.text.chars().count() > 7100 {
-            //should be 7089, but 7100 just for safety
-            eprintln!("Error: number of characters cannot fit a QR code.");
-            exit(0);
+    #[test]
+    fn segmentation_per_count_indicator_class() {
+        let mut builder = QrOptions::builder();
+        builder.ecc(ECCLevel::Low);
+        let options = builder.build().unwrap();
+
+        //more runs of digits pay for a numeric segment of their own with the shorter count indicators
+        //of the lower class, segmenting once with the longest ones needs a version more
+        for (text, version) in [("ab7777777".repeat(26), 9), ("ab77777777".repeat(138), 26)] {
+            let generator = Generator::new(text.clone(), options.clone());
+            let once = generator.text_segments(&text, 40).unwrap();
+            assert_eq!(generator.fit_version(&once, 1..=40), version + 1);
+
+            let (preflight, segments) = generator.plan().unwrap();
+            assert_eq!(preflight.version(), version);
+            assert!(segments.len() > once.len());
+            assert_eq!(generator.run().unwrap().version(), version);
         }
+    }
 
-        let mut stream = BitStream::new();
+    //a generator asked for the level with the ECC boost on, for a run of alphanumeric characters
+    fn boosted(ecc: ECCLevel, len: usize) -> Generator {
+        let mut builder = QrOptions::builder();
+        builder.ecc(ecc).boost_ecc(true);
+        Generator::new("A".repeat(len), builder.build().unwrap())
+    }
 
-        let (version, encoding) = self.get_version();
-        if version == 0 || version > 40 {
-            eprintln!(
-                "Error: {}",
-                if version > 40 {
-                    "number of characters cannot fit a QR code. Consider choosing a lower error correction level."
-                } else {
-                    "no characters found."
-                }
-            );
-            exit(0);
+    #[test]
+    fn boost_ecc_keeps_the_version() {
+        //a version 1 symbol holds 25, 20, 16 and 10 alphanumeric characters at L, M, Q and H
+        for (len, ecc) in [
+            (25, ECCLevel::Low),
+            (20, ECCLevel::Medium),
+            (16, ECCLevel::Quartile),
+            (10, ECCLevel::High),
+        ] {
+            let preflight = boosted(ECCLevel::Low, len).preflight().unwrap();
+            assert_eq!((preflight.version(), preflight.ecc_level()), (1, ecc));
+
+            let symbol = boosted(ECCLevel::Low, len).run().unwrap();
+            assert_eq!((symbol.version(), symbol.ecc_level()), (1, ecc));
         }
 
-        let mut chars = self.text.chars();
+        //without the option the level stays where it was asked for
+        let preflight = Generator::new("A".to_string(), options(1))
+            .preflight()
+            .unwrap();
+        assert_eq!(preflight.ecc_level(), ECCLevel::Low);
+    }
 
-        if self.flag.bytes {
-            BytesEncoder::encode(&mut chars, self.text.chars().count(), &mut stream, version);
-            // println!("\nlength: {}; version: {version}", self.text.len());
-            // stream.debug_print();
-        } else {
-            // println!("{encoding:?}");
-            for (len, mode) in encoding {
-                match mode {
-                    0 => NumeralEncoder::encode(&mut chars, len as usize, &mut stream, version),
-                    1 => AlphanumEncoder::encode(&mut chars, len as usize, &mut stream, version),
-                    2 => BytesEncoder::encode(&mut chars, len as usize, &mut stream, version),
-                    3 => KanjiEncoder::encode(&mut chars, len as usize, &mut stream, version),
-                    _ => {
-                        eprintln!("Error occurred during qr code generation parsing: weird mode {mode}");
-                        exit(0);
-                    }
-                }
-            }
+    #[test]
+    fn boost_ecc_never_lowers() {
+        //17 characters only fit version 2 at Q, which still holds them at H
+        let preflight = boosted(ECCLevel::Quartile, 17).preflight().unwrap();
+        assert_eq!(
+            (preflight.version(), preflight.ecc_level()),
+            (2, ECCLevel::High)
+        );
 
-            // println!("\nversion: {version}");
-            // stream.debug_print();
+        let rank = |ecc| {
+            ECCLevel::ALL
+                .iter()
+                .position(|&level| level == ecc)
+                .unwrap()
+        };
+        for ecc in ECCLevel::ALL {
+            let mut builder = QrOptions::builder();
+            builder.ecc(ecc);
+            let options = builder.build().unwrap();
+            for len in (1..300).step_by(23) {
+                let plain = Generator::new("A".repeat(len), options.clone())
+                    .preflight()
+                    .unwrap();
+                let preflight = boosted(ecc, len).preflight().unwrap();
+                assert_eq!(preflight.version(), plain.version());
+                assert!(rank(preflight.ecc_level()) >= rank(ecc));
+            }
         }
+    }
 
-        if stream.size() <= qr_capacity_query(&self.flag.ecc, version) - 4 {
-            stream.push_bits(0, 4);
+    #[test]
+    fn arbitrary_bytes_round_trip() {
+        let all = (0..=255).collect::<Vec<u8>>();
+        let invalid_utf8 = vec![0xC3, 0x28, 0x00, 0xFF, 0xFE, 0x80, 0x0A];
+        for data in [all, invalid_utf8] {
+            let symbol = crate::encode_bytes(&data, &QrOptions::default()).unwrap();
+            let codewords = data_codewords(&symbol);
+            let bit = |idx: usize| (codewords[idx / 8] >> (7 - idx % 8)) & 1;
+            let read = |start: usize, len: usize| {
+                (start..start + len).fold(0, |value, idx| (value << 1) | bit(idx) as usize)
+            };
+
+            //a single byte segment without an ECI, its count indicator is 8 bits below version 10
+            let count_len = if symbol.version() < 10 { 8 } else { 16 };
+            assert_eq!(read(0, 4), 0b0100);
+            assert_eq!(read(4, count_len), data.len());
+            let octets = (0..data.len())
+                .map(|idx| read(4 + count_len + 8 * idx, 8) as u8)
+                .collect::<Vec<_>>();
+            assert_eq!(octets, data);
         }
+    }
 
-        //obtain the data blocks
-        let (mut blocks, mut blocks_num) = BlockDivision::new().consume(version, &self.flag.ecc);
-        blocks.reverse();
-        blocks_num.reverse();
-        let (data, _) = stream.consume();
-        
-        let data_size = data.len();
-        let mut idx = 0;
-        let mut data_codewords = Vec::new();
-        while !blocks.is_empty() {
-            let last_idx = blocks_num.last_mut().unwrap();
-            *last_idx -= 1;
-
-            if idx < data_size {
-                let (total_len, data_len, _) = *blocks.last().unwrap();
-                let mut data_vec = Vec::new();
-                let old_idx = idx;
-                while idx < data_size && idx - old_idx < data_len {
-                    data_vec.push(data[idx]);
-                    idx += 1;
-                }
+    #[test]
+    fn hand_built_segments() {
+        let segments = [
+            Segment::bytes("sn"),
+            Segment::numeric("0123456789").unwrap(),
+            Segment::alphanumeric("AB-12").unwrap(),
+            Segment::binary(&[0xFF, 0x00]),
+        ];
+        let symbol = crate::encode_segments(&segments, &options(1)).unwrap();
+        assert_eq!(symbol.segments(), segments);
 
-                if data_vec.len() < data_len {
-                    while data_vec.len() + 2 <= data_len {
-                        data_vec.push(0xEC);
-                        data_vec.push(0x11);
-                    }
+        //the mode indicators and counts in the given order, with the version 1 count indicators
+        let codewords = data_codewords(&symbol);
+        let bit = |idx: usize| (codewords[idx / 8] >> (7 - idx % 8)) & 1;
+        let read = |start: usize, len: usize| {
+            (start..start + len).fold(0, |value, idx| (value << 1) | bit(idx) as usize)
+        };
+        let mut pos = 0;
+        let mut written = vec![];
+        for _ in 0..segments.len() {
+            let mode = read(pos, 4);
+            let count_len = match mode {
+                0b0001 => 10,
+                0b0010 => 9,
+                _ => 8,
+            };
+            let count = read(pos + 4, count_len);
+            let data_len = match mode {
+                0b0001 => 10 * (count / 3) + [0, 4, 7][count % 3],
+                0b0010 => 11 * (count / 2) + 6 * (count % 2),
+                _ => 8 * count,
+            };
+            written.push((mode, count));
+            pos += 4 + count_len + data_len;
+        }
+        assert_eq!(
+            written,
+            [(0b0100, 2), (0b0001, 10), (0b0010, 5), (0b0100, 2)]
+        );
+        assert_eq!(read(pos, 4), 0);
+    }
 
-                    if data_vec.len() < data_len && data_vec.len() + 2 != data_len {
-                        data_vec.push(0xEC);
-                    }
-                }
+    #[test]
+    fn segment_mode_mismatch() {
+        assert!(matches!(
+            Segment::numeric("12a"),
+            Err(QrError::UnencodableCharacter {
+                ch: 'a',
+                mode: Mode::Numeric
+            })
+        ));
+        assert!(matches!(
+            Segment::new(Mode::Alphanumeric, "AB-c"),
+            Err(QrError::UnencodableCharacter {
+                ch: 'c',
+                mode: Mode::Alphanumeric
+            })
+        ));
+        assert!(matches!(
+            Segment::new(Mode::Kanji, "A"),
+            Err(QrError::UnencodableCharacter {
+                ch: 'A',
+                mode: Mode::Kanji
+            })
+        ));
+    }
 
-                data_codewords.push((total_len - data_len, data_vec));
-            } else {
-                let (total_len, data_len, _) = *blocks.last().unwrap();
-                let mut data_vec = Vec::new();
-                
-                while data_vec.len() + 2 <= data_len {
-                    data_vec.push(0xEC);
-                    data_vec.push(0x11);
-                }
+    #[test]
+    fn module_kinds() {
+        use ModuleKind::*;
 
-                if data_vec.len() < data_len && data_vec.len() + 2 != data_len {
-                    data_vec.push(0xEC);
-                }
+        let mut builder = QrOptions::builder();
+        builder.ecc(ECCLevel::Low).version(7).mask(0);
+        let symbol = Generator::new(String::from("MODULES"), builder.build().unwrap())
+            .run()
+            .unwrap();
+        let size = symbol.size();
+        assert_eq!(size, 45);
+
+        //(x, y, kind, dark) at fixed positions of a version 7 symbol
+        let known = [
+            (0, 0, Finder, true),
+            (1, 1, Finder, false),
+            (3, 3, Finder, true),
+            (size - 1, 6, Finder, true),
+            (0, size - 7, Finder, true),
+            (7, 7, Separator, false),
+            (size - 8, 0, Separator, false),
+            (0, size - 8, Separator, false),
+            (8, 6, Timing, true),
+            (9, 6, Timing, false),
+            (6, 10, Timing, true),
+            (6, 11, Timing, false),
+            (22, 22, Alignment, true),
+            (21, 22, Alignment, false),
+            (20, 22, Alignment, true),
+            (22, 6, Alignment, true),
+            (8, size - 8, Format, true),
+            (size - 1, size - 1, Data, symbol.is_dark(size - 1, size - 1)),
+        ];
+        for (x, y, kind, dark) in known {
+            assert_eq!((x, y, symbol.module_kind(x, y)), (x, y, kind));
+            assert_eq!((x, y, symbol.is_dark(x, y)), (x, y, dark));
+        }
 
-                data_codewords.push((total_len - data_len, data_vec));
+        //the format information of L with mask 0 is 111011111000100, least significant bit first
+        //down column 8 and left along row 8 around the top left finder, and left along row 8 from
+        //the right edge then down column 8 to the bottom edge
+        let format_info = 0b111011111000100;
+        for idx in 0..15 {
+            let first = match idx {
+                0..=5 => (8, idx),
+                6 => (8, 7),
+                7 => (8, 8),
+                8 => (7, 8),
+                _ => (14 - idx, 8),
+            };
+            let second = if idx < 8 {
+                (size - 1 - idx, 8)
+            } else {
+                (8, size - 15 + idx)
+            };
+            let dark = (format_info >> idx) & 1 == 1;
+            for (x, y) in [first, second] {
+                assert_eq!(symbol.module_kind(x, y), Format);
+                assert_eq!((idx, symbol.is_dark(x, y)), (idx, dark));
             }
+        }
 
-            if *last_idx == 0 {
-                blocks.pop();
-                blocks_num.pop();
+        //the version information of version 7 is 000111 110010 010100, least significant bit first
+        //down the columns of the block left of the bottom left finder and across the rows of the
+        //block above the top right one
+        let version_info = 0b000111_110010_010100;
+        for idx in 0..18 {
+            let (a, b) = (idx / 3, size - 11 + idx % 3);
+            let dark = (version_info >> idx) & 1 == 1;
+            for (x, y) in [(a, b), (b, a)] {
+                assert_eq!(symbol.module_kind(x, y), Version);
+                assert_eq!((idx, symbol.is_dark(x, y)), (idx, dark));
             }
         }
 
-        //obtain the error correction blocks
-        let err = ErrorCorrection::new();
-        let mut error_codewords = Vec::new();
-        for (err_len, code_vec) in &data_codewords {
-            error_codewords.push(err.calculate(code_vec, *err_len));
-        }
-
-        // for (_, vec) in &data_codewords {
-        //     println!("{} | ", vec.len());
-        //     for dat in vec {
-        //         print!("{:08b} ", dat);
-        //     }
-        //     println!();
-        // }
-        // println!("----------------------");
-        // for vec in &error_codewords {
-        //     println!("{} |", vec.len());
-        //     for dat in vec {
-        //         print!("{:08b} ", dat);
-        //     }
-        //     println!();
-        // }
-        // println!("----------------------");
-        
-        let qr_code_data = Self::combine_data_err(data_codewords, error_codewords);
-        // for dat in &qr_code_data {
-        //       print!("{:08b} ", dat);
-        // }
-        // println!("{}", qr_code_data.len());
-        let qr_code = QRCode::new(qr_code_data, version, &self.flag.ecc);
-        qr_code.gen_image(self.size).save(&self.output).unwrap();
-
-        println!("QR Code generated as '{}'. (version: {version})", self.output);
+        //version 7-L has 156 data codewords of 196 and no remainder bits
+        let count = |kind| {
+            symbol
+                .modules()
+                .filter(|module| module.kind == kind)
+                .count()
+        };
+        assert_eq!(
+            (count(Data), count(Ecc), count(Remainder)),
+            (8 * 156, 8 * 40, 0)
+        );
+        assert_eq!(count(Version), 36);
+        assert_eq!(count(Format), 31);
+
+        let modules = symbol.modules().collect::<Vec<_>>();
+        assert_eq!(modules.len(), size * size);
+        for (idx, module) in modules.iter().enumerate() {
+            assert_eq!((module.x, module.y), (idx % size, idx / size));
+            assert_eq!(module.dark, symbol.is_dark(module.x, module.y));
+        }
+
+        //version 2 leaves 7 remainder bits
+        let symbol = Generator::new(String::from("MODULES"), options(2))
+            .run()
+            .unwrap();
+        let count = symbol
+            .modules()
+            .filter(|module| module.kind == Remainder)
+            .count();
+        assert_eq!(count, 7);
     }
 }
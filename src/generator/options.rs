@@ -0,0 +1,290 @@
+use super::{Charset, ECCLevel, Fnc1};
+use crate::error::QrError;
+
+/// configuration shared by the command line and library users, built through `QrOptions::builder()`
+#[derive(Debug, Clone)]
+pub struct QrOptions {
+    ecc: ECCLevel,
+    min_version: u8,
+    max_version: u8,
+    mask: Option<u8>,
+    force_bytes: bool,
+    charset: Option<Charset>,
+    fnc1: Option<Fnc1>,
+    hanzi: bool,
+    boost_ecc: bool,
+    //rMQR symbol bounds in modules, `None` for no bound
+    max_height: Option<usize>,
+    max_width: Option<usize>,
+    //`None` for the width the symbol type asks for
+    quiet_zone: Option<u32>,
+    scale: u32,
+}
+
+impl Default for QrOptions {
+    fn default() -> Self {
+        Self {
+            ecc: ECCLevel::Quartile,
+            min_version: 1,
+            max_version: 40,
+            mask: None,
+            force_bytes: false,
+            charset: None,
+            fnc1: None,
+            hanzi: false,
+            boost_ecc: false,
+            max_height: None,
+            max_width: None,
+            quiet_zone: None,
+            scale: 5,
+        }
+    }
+}
+
+impl QrOptions {
+    pub fn builder() -> QrOptionsBuilder {
+        QrOptionsBuilder {
+            options: Self::default(),
+        }
+    }
+
+    pub fn ecc(&self) -> ECCLevel {
+        self.ecc
+    }
+
+    pub fn min_version(&self) -> u8 {
+        self.min_version
+    }
+
+    pub fn max_version(&self) -> u8 {
+        self.max_version
+    }
+
+    /// the mask pattern pinned by the user, `None` if the penalty evaluation decides
+    pub fn mask(&self) -> Option<u8> {
+        self.mask
+    }
+
+    /// true if the whole input is put into a single byte segment
+    pub fn force_bytes(&self) -> bool {
+        self.force_bytes
+    }
+
+    /// the charset of byte mode text, `None` for Latin-1 when every character fits, UTF-8 otherwise
+    pub fn charset(&self) -> Option<Charset> {
+        self.charset
+    }
+
+    /// the FNC1 mode the symbol is in, `None` for plain data
+    pub fn fnc1(&self) -> Option<Fnc1> {
+        self.fnc1
+    }
+
+    /// true if GB2312 text may be written in hanzi mode
+    pub fn hanzi(&self) -> bool {
+        self.hanzi
+    }
+
+    /// true if the error correction level is raised above `ecc` as far as the data still fits the version
+    pub fn boost_ecc(&self) -> bool {
+        self.boost_ecc
+    }
+
+    /// the most modules an rMQR symbol may be high, `None` for any height
+    pub fn max_height(&self) -> Option<usize> {
+        self.max_height
+    }
+
+    /// the most modules an rMQR symbol may be wide, `None` for any width
+    pub fn max_width(&self) -> Option<usize> {
+        self.max_width
+    }
+
+    /// the same options with the charset pinned
+    pub(crate) fn with_charset(&self, charset: Charset) -> Self {
+        Self {
+            charset: Some(charset),
+            ..self.clone()
+        }
+    }
+
+    /// the same options in FNC1 mode
+    pub(crate) fn with_fnc1(&self, fnc1: Fnc1) -> Self {
+        Self {
+            fnc1: Some(fnc1),
+            ..self.clone()
+        }
+    }
+
+    /// width of the light border around the symbol in modules, 4 unless set
+    pub fn quiet_zone(&self) -> u32 {
+        self.quiet_zone.unwrap_or(4)
+    }
+
+    /// width of the light border around a Micro QR or rMQR symbol in modules, 2 unless set
+    pub fn micro_quiet_zone(&self) -> u32 {
+        self.quiet_zone.unwrap_or(2)
+    }
+
+    /// size of each module in pixels
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QrOptionsBuilder {
+    options: QrOptions,
+}
+
+impl QrOptionsBuilder {
+    pub fn ecc(&mut self, ecc: ECCLevel) -> &mut Self {
+        self.options.ecc = ecc;
+        self
+    }
+
+    pub fn min_version(&mut self, version: u8) -> &mut Self {
+        self.options.min_version = version;
+        self
+    }
+
+    pub fn max_version(&mut self, version: u8) -> &mut Self {
+        self.options.max_version = version;
+        self
+    }
+
+    /// sets both the minimum and maximum version
+    pub fn version(&mut self, version: u8) -> &mut Self {
+        self.options.min_version = version;
+        self.options.max_version = version;
+        self
+    }
+
+    pub fn mask(&mut self, mask: u8) -> &mut Self {
+        self.options.mask = Some(mask);
+        self
+    }
+
+    pub fn force_bytes(&mut self, force_bytes: bool) -> &mut Self {
+        self.options.force_bytes = force_bytes;
+        self
+    }
+
+    pub fn charset(&mut self, charset: Charset) -> &mut Self {
+        self.options.charset = Some(charset);
+        self
+    }
+
+    /// in FNC1 mode a `GS` character (0x1D) separates fields, see `Fnc1`
+    pub fn fnc1(&mut self, fnc1: Fnc1) -> &mut Self {
+        self.options.fnc1 = Some(fnc1);
+        self
+    }
+
+    /// lets the segmentation use hanzi mode for GB2312 characters, off by default since
+    /// not every scanner reads it
+    pub fn hanzi(&mut self, hanzi: bool) -> &mut Self {
+        self.options.hanzi = hanzi;
+        self
+    }
+
+    /// once the smallest version holding the data at `ecc` is found, raises the level
+    /// (L, M, Q then H) as far as the data still fits that version instead of padding it,
+    /// `QRCode::ecc_level` tells the level used
+    pub fn boost_ecc(&mut self, boost_ecc: bool) -> &mut Self {
+        self.options.boost_ecc = boost_ecc;
+        self
+    }
+
+    /// limits the rMQR symbol to at most `modules` high (7 to 17), for narrow labels,
+    /// the symbol of the smallest area within the limits is chosen
+    pub fn max_height(&mut self, modules: usize) -> &mut Self {
+        self.options.max_height = Some(modules);
+        self
+    }
+
+    /// limits the rMQR symbol to at most `modules` wide (27 to 139)
+    pub fn max_width(&mut self, modules: usize) -> &mut Self {
+        self.options.max_width = Some(modules);
+        self
+    }
+
+    pub fn quiet_zone(&mut self, modules: u32) -> &mut Self {
+        self.options.quiet_zone = Some(modules);
+        self
+    }
+
+    pub fn scale(&mut self, pixels: u32) -> &mut Self {
+        self.options.scale = pixels;
+        self
+    }
+
+    /// validates the options, so that nothing past this point has to
+    pub fn build(&self) -> Result<QrOptions, QrError> {
+        let options = &self.options;
+
+        for (name, version) in [("minimum", options.min_version), ("maximum", options.max_version)] {
+            if !(1..=40).contains(&version) {
+                return Err(QrError::InvalidOption(format!(
+                    "{} version must be 1 to 40 inclusive but given '{}'.",
+                    name, version
+                )));
+            }
+        }
+
+        if options.min_version > options.max_version {
+            return Err(QrError::InvalidOption(format!(
+                "minimum version {} is larger than maximum version {}.",
+                options.min_version, options.max_version
+            )));
+        }
+
+        if let Some(mask) = options.mask {
+            if mask > 7 {
+                return Err(QrError::InvalidOption(format!(
+                    "mask must be 0 to 7 inclusive but given '{}'.",
+                    mask
+                )));
+            }
+        }
+
+        if let Some(charset) = options.charset {
+            if !charset.is_valid() {
+                return Err(QrError::InvalidOption(format!("{:?} is not a charset.", charset)));
+            }
+
+            if !cfg!(feature = "charsets") && !charset.is_builtin() {
+                return Err(QrError::InvalidOption(format!(
+                    "{} needs the charsets feature.",
+                    charset
+                )));
+            }
+        }
+
+        if options.hanzi && !cfg!(feature = "hanzi") {
+            return Err(QrError::InvalidOption(String::from(
+                "hanzi mode needs the hanzi feature.",
+            )));
+        }
+
+        for (name, limit, min) in [
+            ("height", options.max_height, 7),
+            ("width", options.max_width, 27),
+        ] {
+            if limit.is_some_and(|limit| limit < min) {
+                return Err(QrError::InvalidOption(format!(
+                    "maximum rMQR {} must be at least {} modules.",
+                    name, min
+                )));
+            }
+        }
+
+        if options.scale == 0 {
+            return Err(QrError::InvalidOption(String::from(
+                "pixel size must be at least 1.",
+            )));
+        }
+
+        Ok(options.clone())
+    }
+}
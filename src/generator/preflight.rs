@@ -0,0 +1,146 @@
+use super::{data::qr_capacity_query, ECCLevel, Mode};
+
+/// the exact size of the data and the version it gets, worked out without encoding anything
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preflight {
+    version: u8,
+    ecc: ECCLevel,
+    bits: usize,
+    capacity: usize,
+    //characters of each mode that can still be appended, in `Mode::ALL` order
+    remaining: [usize; 5],
+}
+
+impl Preflight {
+    pub(super) fn new(version: u8, ecc: ECCLevel, bits: usize) -> Self {
+        Self {
+            version,
+            ecc,
+            bits,
+            capacity: qr_capacity_query(&ecc, version),
+            remaining: [0; 5],
+        }
+    }
+
+    /// sets the characters of each mode that can still be appended, in `Mode::ALL` order
+    pub(super) fn with_remaining(self, remaining: [usize; 5]) -> Self {
+        Self { remaining, ..self }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn ecc_level(&self) -> ECCLevel {
+        self.ecc
+    }
+
+    /// number of data bits, ECI, mode and count indicators included (the terminator and padding are not)
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// number of data bits the version holds at the error correction level
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn remaining_bits(&self) -> usize {
+        self.capacity - self.bits
+    }
+
+    /// how many characters of `mode` (octets for byte mode) can still be appended to the payload
+    /// without a larger version or a lower level, the text being segmented again with them.
+    /// 0 for the modes the symbol cannot use (hanzi unless the options turn it on)
+    pub fn remaining_chars(&self, mode: Mode) -> usize {
+        let idx = match mode {
+            Mode::Numeric => 0,
+            Mode::Alphanumeric => 1,
+            Mode::Byte => 2,
+            Mode::Kanji => 3,
+            Mode::Hanzi => 4,
+        };
+        self.remaining[idx]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{Generator, QrOptions};
+
+    fn preflight(text: &str, hanzi: bool) -> Preflight {
+        let mut builder = QrOptions::builder();
+        builder.hanzi(hanzi);
+        Generator::new(String::from(text), builder.build().unwrap())
+            .preflight()
+            .unwrap()
+    }
+
+    #[cfg(all(feature = "kanji", feature = "hanzi"))]
+    #[test]
+    fn remaining_chars_are_exact() {
+        let samples = [
+            (Mode::Numeric, '7'),
+            (Mode::Alphanumeric, 'Z'),
+            (Mode::Byte, 'z'),
+            (Mode::Kanji, '漢'),
+            (Mode::Hanzi, '汉'),
+        ];
+
+        for text in [
+            "12345",
+            "ABC123abc",
+            "HELLO WORLD",
+            "漢字 and 12 digits",
+            "a",
+        ] {
+            for hanzi in [false, true] {
+                let first = preflight(text, hanzi);
+                for (mode, ch) in samples {
+                    if mode == Mode::Hanzi && !hanzi {
+                        assert_eq!(first.remaining_chars(mode), 0);
+                        continue;
+                    }
+                    let remaining = first.remaining_chars(mode);
+
+                    //`remaining` more characters still fit the version, one more does not
+                    let fits = |count: usize| {
+                        let extended = text.chars().chain(core::iter::repeat_n(ch, count));
+                        preflight(&extended.collect::<String>(), hanzi).version() == first.version()
+                    };
+                    assert!(fits(remaining), "{text} {mode:?}");
+                    assert!(!fits(remaining + 1), "{text} {mode:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn resegmented_text() {
+        //version 1-Q (104 bits): the digits move to alphanumeric mode along with the appended characters
+        assert_eq!(
+            preflight("12345", false).remaining_chars(Mode::Alphanumeric),
+            11
+        );
+        assert_eq!(preflight("ABC123abc", false).remaining_chars(Mode::Byte), 2);
+    }
+
+    #[cfg(feature = "hanzi")]
+    #[test]
+    fn hanzi_only_when_allowed() {
+        assert_eq!(preflight("12345", false).remaining_chars(Mode::Hanzi), 0);
+        assert!(preflight("12345", true).remaining_chars(Mode::Hanzi) > 0);
+    }
+
+    #[test]
+    fn fixed_segments() {
+        //the appended octets extend the last byte segment: 12 of the 104 bits of version 1-Q are left
+        let options = QrOptions::builder().build().unwrap();
+        let preflight = Generator::from_bytes(vec![0xFF; 10], options)
+            .preflight()
+            .unwrap();
+        assert_eq!(preflight.version(), 1);
+        assert_eq!(preflight.remaining_chars(Mode::Byte), 1);
+    }
+}
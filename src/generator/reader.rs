@@ -0,0 +1,160 @@
+use super::{
+    charset::Charset,
+    codewords::{interleaved_order, total_codewords},
+    data::qr_capacity_query,
+    matrix::{Layout, MASKS},
+    ECCLevel, Mode,
+};
+
+const ALPHANUM: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+//reads the data codewords front to back, most significant bit first
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn remaining(&self) -> usize {
+        8 * self.bytes.len() - self.pos
+    }
+
+    fn take(&mut self, len: usize) -> Option<usize> {
+        if len > self.remaining() {
+            return None;
+        }
+
+        let mut value = 0;
+        for _ in 0..len {
+            let bit = (self.bytes[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+            value = (value << 1) | bit as usize;
+            self.pos += 1;
+        }
+
+        Some(value)
+    }
+}
+
+//true for the octets text does not hold, the C0 controls but tab, line feed and carriage return,
+//DEL and the C1 controls
+fn is_binary(byte: u8) -> bool {
+    matches!(byte, 0x00..=0x08 | 0x0B | 0x0C | 0x0E..=0x1F | 0x7F..=0x9F)
+}
+
+/// the text of a symbol, each byte segment decoded in the charset the last ECI announced,
+/// ISO-8859-1 (ECI 3) before any
+///
+/// `raw` holds the masked data bits in placement order and `octets` the payload the scanner decoded
+/// after error correction (byte segments as they are, kanji as Shift JIS). Returns `None` for binary
+/// data, byte segments without an ECI holding control octets text does not (the octets are kept as
+/// they are), or if the bits read here, which are not error corrected, do not give the same octets.
+pub(crate) fn symbol_text(
+    version: u8,
+    ecc: ECCLevel,
+    mask: u8,
+    raw: &[u8],
+    octets: &[u8],
+) -> Option<String> {
+    let total = total_codewords(version);
+    let mut placed = vec![0u8; total];
+    for (idx, (x, y)) in Layout::new(version)
+        .data_positions()
+        .take(8 * total)
+        .enumerate()
+    {
+        let bit = (raw.get(idx / 8)? >> (7 - idx % 8)) & 1 == 1;
+        if bit != MASKS[mask as usize](y, x) {
+            placed[idx / 8] |= 0x80 >> (idx % 8);
+        }
+    }
+
+    let mut codewords = vec![0u8; total];
+    for (idx, src) in interleaved_order(version, &ecc).enumerate() {
+        codewords[src] = placed[idx];
+    }
+
+    let mut reader = BitReader {
+        bytes: &codewords[..qr_capacity_query(&ecc, version) / 8],
+        pos: 0,
+    };
+    let mut read = Vec::new();
+    let mut text = String::new();
+    let mut charset = None;
+
+    while reader.remaining() >= 4 {
+        match reader.take(4)? {
+            0b0000 => break,
+            0b0001 => {
+                let mut len = reader.take(Mode::Numeric.count_bits(version))?;
+                while len > 0 {
+                    let digits = len.min(3);
+                    let value = reader.take([4, 7, 10][digits - 1])?;
+                    let group = format!("{:01$}", value, digits);
+                    read.extend_from_slice(group.as_bytes());
+                    text.push_str(&group);
+                    len -= digits;
+                }
+            }
+            0b0010 => {
+                let len = reader.take(Mode::Alphanumeric.count_bits(version))?;
+                let mut chars = Vec::new();
+                for _ in 0..len / 2 {
+                    let value = reader.take(11)?;
+                    chars.extend([value / 45, value % 45]);
+                }
+                if len % 2 == 1 {
+                    chars.push(reader.take(6)?);
+                }
+
+                for value in chars {
+                    let ch = *ALPHANUM.get(value)?;
+                    read.push(ch);
+                    text.push(ch as char);
+                }
+            }
+            0b0100 => {
+                let len = reader.take(Mode::Byte.count_bits(version))?;
+                let bytes = (0..len)
+                    .map(|_| reader.take(8).map(|byte| byte as u8))
+                    .collect::<Option<Vec<u8>>>()?;
+                if charset.is_none() && bytes.iter().any(|&byte| is_binary(byte)) {
+                    return None;
+                }
+
+                read.extend_from_slice(&bytes);
+                text.push_str(&charset.unwrap_or(Charset::Latin1).decode(&bytes));
+            }
+            0b1000 => {
+                let len = reader.take(Mode::Kanji.count_bits(version))?;
+                let mut bytes = Vec::new();
+                for _ in 0..len {
+                    let value = reader.take(13)?;
+                    let value = ((value / 0xC0) << 8) | (value % 0xC0);
+                    let value = if value + 0x8140 <= 0x9FFC {
+                        value + 0x8140
+                    } else {
+                        value + 0xC140
+                    };
+                    bytes.extend([(value >> 8) as u8, value as u8]);
+                }
+                read.extend_from_slice(&bytes);
+                text.push_str(&Charset::ShiftJis.decode(&bytes));
+            }
+            0b0111 => {
+                let first = reader.take(8)?;
+                let designator = if first & 0x80 == 0 {
+                    first
+                } else if first & 0xC0 == 0x80 {
+                    ((first & 0x3F) << 8) | reader.take(8)?
+                } else {
+                    ((first & 0x1F) << 16) | reader.take(16)?
+                };
+
+                charset = Some(Charset::from_eci(designator as u32)?);
+            }
+            _ => return None,
+        }
+    }
+
+    (read == octets).then_some(text)
+}
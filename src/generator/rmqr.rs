@@ -0,0 +1,673 @@
+#[cfg(any(feature = "render-png", feature = "render-jpeg"))]
+use image::{ImageBuffer, Rgba};
+
+#[cfg(any(feature = "render-png", feature = "render-jpeg"))]
+use super::qr::render;
+use super::{
+    bitstream::BitStream,
+    data::{obtain_rmqr_alignment, rmqr_block, rmqr_count_bits, rmqr_size},
+    ecc::{version_information_process, ErrorCorrection},
+    matrix::{stride, MASKS},
+    Charset, ECCLevel, Generator, Mode, Segment,
+};
+use crate::error::QrError;
+
+//the format information is masked differently beside the finder and beside the sub-finder
+const FINDER_FORMAT_MASK: u32 = 0b011111101010110010;
+const SUB_FINDER_FORMAT_MASK: u32 = 0b100000101001111011;
+
+//rMQR has a single mask pattern, QR code mask 4
+const RMQR_MASK: usize = 4;
+
+/// size of the whole header and of the character count indicator of `mode` in rMQR version `version`,
+/// `None` for hanzi, which rMQR does not have
+///
+/// the mode indicator is one more than the number of the mode (see `Mode::from_index`) in 3 bits
+fn header(mode: Mode, version: u8) -> Option<(usize, usize)> {
+    let count_bits = *rmqr_count_bits(version).get(mode as usize)?;
+    Some((3 + count_bits, count_bits))
+}
+
+/// the level rMQR symbols are made at for `ecc`, rMQR only has M and H
+fn level(ecc: ECCLevel) -> ECCLevel {
+    match ecc {
+        ECCLevel::Low | ECCLevel::Medium => ECCLevel::Medium,
+        ECCLevel::Quartile | ECCLevel::High => ECCLevel::High,
+    }
+}
+
+/// computes the error correction codewords of each of the `blocks` blocks `codewords` holds
+/// and returns them all in placement order, the data codewords of the blocks in turn, then their error correction codewords
+fn interleave(codewords: &mut [u8], blocks: usize, data_len: usize) -> Vec<u8> {
+    let total = codewords.len();
+    let err_len = (total - data_len) / blocks;
+    let short_len = data_len / blocks;
+    let short_num = blocks - data_len % blocks;
+    //the blocks with one more data codeword come last
+    let start = |block: usize| block * short_len + block.saturating_sub(short_num);
+
+    let (data, err) = codewords.split_at_mut(data_len);
+    let calc = ErrorCorrection::new();
+    for block in 0..blocks {
+        calc.calculate(
+            &data[start(block)..start(block + 1)],
+            &mut err[block * err_len..(block + 1) * err_len],
+        );
+    }
+
+    let mut placed = Vec::with_capacity(total);
+    for idx in 0..=short_len {
+        for block in 0..blocks {
+            if start(block) + idx < start(block + 1) {
+                placed.push(data[start(block) + idx]);
+            }
+        }
+    }
+
+    for idx in 0..err_len {
+        for block in 0..blocks {
+            placed.push(err[block * err_len + idx]);
+        }
+    }
+
+    placed
+}
+
+impl Generator {
+    /// encodes the payload into the rMQR symbol (R7x43 to R17x139) of the smallest area holding it,
+    /// within the maximum height and width of the options
+    ///
+    /// Like Micro QR, no ECI or FNC1 is written so byte mode text is always Latin-1.
+    /// rMQR only has levels M and H, L is raised to M and Q to H.
+    /// There is a single mask pattern, so a mask cannot be pinned.
+    pub fn run_rmqr(self) -> Result<RMQRCode, QrError> {
+        if self.options.mask().is_some() {
+            return Err(QrError::InvalidOption(String::from(
+                "rMQR has a single mask pattern, it cannot be chosen.",
+            )));
+        }
+
+        let ecc = level(self.options.ecc());
+
+        //the sort is stable, symbols of the same area stay in version indicator order
+        let mut versions = (0..32)
+            .filter(|&version| {
+                let (height, width) = rmqr_size(version);
+                self.options.max_height().is_none_or(|max| height <= max)
+                    && self.options.max_width().is_none_or(|max| width <= max)
+            })
+            .collect::<Vec<u8>>();
+        versions.sort_by_key(|&version| {
+            let (height, width) = rmqr_size(version);
+            height * width
+        });
+
+        let (version, segments) = self.plan_latin1(
+            "rMQR",
+            versions.into_iter().map(|version| {
+                let (_, _, data_len) = rmqr_block(&ecc, version);
+                (
+                    version,
+                    Mode::ALL.map(|mode| header(mode, version)),
+                    8 * data_len,
+                )
+            }),
+        )?;
+
+        let (total, blocks, data_len) = rmqr_block(&ecc, version);
+        let capacity = 8 * data_len;
+        let mut codewords = vec![0u8; total];
+        let mut stream = BitStream::new(&mut codewords[..data_len]);
+
+        for segment in &segments {
+            let count = segment.count(Charset::Latin1);
+            let (_, count_bits) = header(segment.mode(), version).ok_or(QrError::NoVersionFits)?;
+            stream.push_bits(segment.mode() as u8 + 1, 3);
+            stream.push_bits_big(count, count_bits as u8);
+            segment.encode_data(&mut stream, Charset::Latin1)?;
+        }
+
+        if stream.size() > capacity {
+            return Err(QrError::CapacityExceeded {
+                bits: stream.size(),
+                capacity,
+            });
+        }
+
+        //the terminator is cut short if the symbol is full
+        let terminator = 3.min(capacity - stream.size());
+        stream.push_bits(0, terminator as u8);
+
+        let used = stream.len();
+        for (idx, byte) in codewords[used..data_len].iter_mut().enumerate() {
+            *byte = if idx % 2 == 0 { 0xEC } else { 0x11 };
+        }
+
+        let placed = interleave(&mut codewords, blocks, data_len);
+        let bits = placed
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1));
+
+        let mut symbol = RMQRCode::new(version, ecc, segments);
+        symbol.generate(bits);
+        Ok(symbol)
+    }
+}
+
+/// a rectangular Micro QR (rMQR) symbol, 7 to 17 modules high and 27 to 139 wide,
+/// for long narrow spaces a square symbol does not fit
+pub struct RMQRCode {
+    version: u8,
+    ecc: ECCLevel,
+    //row by row, 8 modules per byte as in `QRCode::as_bytes`
+    rows: Vec<u8>,
+    segments: Vec<Segment>,
+}
+
+impl RMQRCode {
+    fn new(version: u8, ecc: ECCLevel, segments: Vec<Segment>) -> Self {
+        let (height, width) = rmqr_size(version);
+        Self {
+            version,
+            ecc,
+            rows: vec![0; height * stride(width)],
+            segments,
+        }
+    }
+
+    /// number of modules across the symbol (quiet zone excluded), 27 to 139
+    pub fn width(&self) -> usize {
+        rmqr_size(self.version).1
+    }
+
+    /// number of modules down the symbol (quiet zone excluded), 7 to 17
+    pub fn height(&self) -> usize {
+        rmqr_size(self.version).0
+    }
+
+    /// the version indicator, 0 for R7x43 to 31 for R17x139
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// `Medium` or `High`, the only levels rMQR has
+    pub fn ecc_level(&self) -> ECCLevel {
+        self.ecc
+    }
+
+    /// the segments the data was encoded in
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// the rows of the symbol, 8 modules per byte (most significant bit first),
+    /// every row starts on a new byte
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.rows
+    }
+
+    /// true if module (x, y) is dark, (0, 0) being the top left module
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.width()
+            && y < self.height()
+            && (self.rows[y * stride(self.width()) + x / 8] >> (7 - x % 8)) & 1 == 1
+    }
+
+    /// renders the symbol with `pixel` pixels per module and a `quiet_zone` modules wide border
+    #[cfg(any(feature = "render-png", feature = "render-jpeg"))]
+    pub fn gen_image(&self, pixel: u32, quiet_zone: u32) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        render(self.width(), self.height(), pixel, quiet_zone, |x, y| {
+            self.is_dark(x, y)
+        })
+    }
+
+    fn set(&mut self, x: usize, y: usize, flag: bool) {
+        let idx = y * stride(self.width()) + x / 8;
+        let byte = &mut self.rows[idx];
+        *byte = *byte & !(1 << (7 - x % 8)) | ((flag as u8) << (7 - x % 8));
+    }
+
+    /// true if (x, y) is part of a finder, timing or alignment pattern, a separator or the format information
+    fn is_function(&self, x: usize, y: usize) -> bool {
+        let (width, height) = (self.width(), self.height());
+        let edge = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+        //finder, separator and format information
+        let finder = (x <= 7 && y <= 7) || (x <= 10 && y <= 5) || (x <= 11 && y <= 3);
+        //sub-finder and format information
+        let sub_finder = (x + 8 >= width && y + 5 >= height)
+            || (x + 8 >= width && x + 3 <= width && y + 6 == height);
+        let corner = (x + 2 >= width && y <= 1) || (x <= 1 && y + 2 >= height);
+        let alignment = obtain_rmqr_alignment(width).iter().any(|&column| {
+            x == column || (x + 1 >= column && x <= column + 1 && (y <= 2 || y + 3 >= height))
+        });
+
+        edge || finder || sub_finder || corner || alignment
+    }
+
+    /// the modules of the encoding region in the order the codeword bits are placed:
+    /// column pairs from the right (the right edge being a timing pattern), going up then down
+    fn data_positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (width, height) = (self.width(), self.height());
+        (1..width - 1)
+            .rev()
+            .step_by(2)
+            .enumerate()
+            .flat_map(move |(pair, x)| {
+                (0..height)
+                    .map(move |idx| if pair % 2 == 0 { height - 1 - idx } else { idx })
+                    .flat_map(move |y| [(x, y), (x - 1, y)])
+            })
+            .filter(|&(x, y)| !self.is_function(x, y))
+    }
+
+    /// draws the function patterns, places the codeword bits (the remainder bits are left light),
+    /// masks them and writes the format information
+    fn generate(&mut self, bits: impl Iterator<Item = bool>) {
+        let (width, height) = (self.width(), self.height());
+        let alignment = obtain_rmqr_alignment(width);
+
+        //timing patterns along the edges and down the alignment columns
+        for x in 0..width {
+            self.set(x, 0, x % 2 == 0);
+            self.set(x, height - 1, x % 2 == 0);
+        }
+
+        for y in 0..height {
+            for &x in [0, width - 1].iter().chain(alignment) {
+                self.set(x, y, y % 2 == 0);
+            }
+        }
+
+        //3x3 alignment patterns at both ends of the alignment columns
+        for &column in alignment {
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    let ring = dx != 1 || dy != 1;
+                    self.set(column + dx - 1, dy, ring);
+                    self.set(column + dx - 1, height - 1 - dy, ring);
+                }
+            }
+        }
+
+        //corner patterns, the finder and its separator cover the bottom left one of R7 and R9 symbols
+        self.set(width - 2, 0, true);
+        self.set(width - 1, 1, true);
+        self.set(width - 2, 1, false);
+        self.set(1, height - 1, true);
+        self.set(0, height - 2, true);
+        self.set(1, height - 2, false);
+
+        for y in 0..7 {
+            for x in 0..7 {
+                let ring = x == 0 || x == 6 || y == 0 || y == 6;
+                let centre = (2..=4).contains(&x) && (2..=4).contains(&y);
+                self.set(x, y, ring || centre);
+            }
+        }
+
+        for idx in 0..8 {
+            if idx < height {
+                self.set(7, idx, false);
+            }
+            if height > 7 {
+                self.set(idx, 7, false);
+            }
+        }
+
+        for dy in 0..5 {
+            for dx in 0..5 {
+                let ring = dx == 0 || dx == 4 || dy == 0 || dy == 4;
+                let centre = dx == 2 && dy == 2;
+                self.set(width - 5 + dx, height - 5 + dy, ring || centre);
+            }
+        }
+
+        let positions = self.data_positions().collect::<Vec<_>>();
+        let mut bits = bits.fuse();
+        for (x, y) in positions {
+            let bit = bits.next().unwrap_or(false);
+            self.set(x, y, bit != MASKS[RMQR_MASK](y, x));
+        }
+
+        self.add_format_symbols();
+    }
+
+    fn add_format_symbols(&mut self) {
+        let (width, height) = (self.width(), self.height());
+        let level = (self.ecc == ECCLevel::High) as u8;
+        let fmt = version_information_process((level << 5) | self.version);
+        let finder = fmt ^ FINDER_FORMAT_MASK;
+        let sub_finder = fmt ^ SUB_FINDER_FORMAT_MASK;
+
+        //down three columns of five right of the finder, then three modules down a fourth
+        for idx in 0..18 {
+            self.set(8 + idx / 5, 1 + idx % 5, (finder >> idx) & 1 == 1);
+        }
+
+        //down three columns of five left of the sub-finder, then rightwards along the row above it
+        for idx in 0..18 {
+            let (x, y) = if idx < 15 {
+                (width - 8 + idx / 5, height - 6 + idx % 5)
+            } else {
+                (width + idx - 20, height - 6)
+            };
+            self.set(x, y, (sub_finder >> idx) & 1 == 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::QrOptions;
+
+    //there is no copy of the ISO/IEC 23941 sample symbol to compare a whole matrix with, so the
+    //layout, format information, codeword placement and version selection are checked on their own
+
+    fn encode(text: &str, ecc: ECCLevel) -> Result<RMQRCode, QrError> {
+        let mut builder = QrOptions::builder();
+        builder.ecc(ecc);
+        Generator::new(String::from(text), builder.build().unwrap()).run_rmqr()
+    }
+
+    //a symbol of every version at both levels, its encoding region left light before masking
+    fn blank_symbols() -> impl Iterator<Item = RMQRCode> {
+        (0..32).flat_map(|version| {
+            [ECCLevel::Medium, ECCLevel::High].map(|ecc| {
+                let mut symbol = RMQRCode::new(version, ecc, Vec::new());
+                symbol.generate(std::iter::empty());
+                symbol
+            })
+        })
+    }
+
+    //the format information of ISO/IEC 23941 uses the BCH(18, 6) code of the QR code version
+    //information, whose codewords for 7 to 40 are listed in ISO/IEC 18004 (table D.1)
+    const VERSION_INFORMATION: [u32; 34] = [
+        0x07C94, 0x085BC, 0x09A99, 0x0A4D3, 0x0BBF6, 0x0C762, 0x0D847, 0x0E60D, 0x0F928, 0x10B78,
+        0x1145D, 0x12A17, 0x13532, 0x149A6, 0x15683, 0x168C9, 0x177EC, 0x18EC4, 0x191E1, 0x1AFAB,
+        0x1B08E, 0x1CC1A, 0x1D33F, 0x1ED75, 0x1F250, 0x209D5, 0x216F0, 0x228BA, 0x2379F, 0x24B0B,
+        0x2542E, 0x26A64, 0x27541, 0x28C69,
+    ];
+
+    //the format information modules from the least significant bit: down columns 8 to 10 from
+    //row 1 to 5 and column 11 from row 1 to 3 beside the finder, and beside the sub-finder up
+    //the columns 8 to 6 modules from the right edge from 6 to 2 modules from the bottom edge,
+    //then rightwards along the row 6 from the bottom edge from 5 to 3 modules from the right edge
+    fn finder_side() -> Vec<(usize, usize)> {
+        [(8, 1..=5), (9, 1..=5), (10, 1..=5), (11, 1..=3)]
+            .into_iter()
+            .flat_map(|(x, rows)| rows.map(move |y| (x, y)))
+            .collect()
+    }
+
+    fn sub_finder_side(width: usize, height: usize) -> Vec<(usize, usize)> {
+        [8, 7, 6]
+            .into_iter()
+            .flat_map(|x| (2..=6).rev().map(move |y| (x, y)))
+            .chain([(5, 6), (4, 6), (3, 6)])
+            .map(|(x, y)| (width - x, height - y))
+            .collect()
+    }
+
+    #[test]
+    fn block_table() {
+        for symbol in blank_symbols() {
+            let (total, blocks, data_len) = rmqr_block(&symbol.ecc, symbol.version);
+            let modules = symbol.data_positions().count();
+            assert_eq!(total, modules / 8, "version {}", symbol.version);
+            assert_eq!((total - data_len) % blocks, 0, "version {}", symbol.version);
+            assert!(data_len >= blocks && data_len < total);
+        }
+
+        //H always has fewer data codewords than M
+        for version in 0..32 {
+            assert!(
+                rmqr_block(&ECCLevel::High, version).2 < rmqr_block(&ECCLevel::Medium, version).2
+            );
+        }
+    }
+
+    #[test]
+    fn format_information() {
+        let mut codewords = Vec::new();
+        for symbol in blank_symbols() {
+            let (width, height) = (symbol.width(), symbol.height());
+            let read = |positions: Vec<(usize, usize)>, mask: u32| {
+                let bits = positions
+                    .iter()
+                    .enumerate()
+                    .fold(0, |bits, (idx, &(x, y))| {
+                        bits | (symbol.is_dark(x, y) as u32) << idx
+                    });
+                bits ^ mask
+            };
+            let finder = read(finder_side(), 0x1FAB2);
+            let sub_finder = read(sub_finder_side(width, height), 0x20A7B);
+            assert_eq!(finder, sub_finder, "version {}", symbol.version);
+
+            //the level (0 for M, 1 for H) and the version indicator in the top 6 bits
+            let level = (symbol.ecc == ECCLevel::High) as u32;
+            let data = (level << 5) | symbol.version as u32;
+            assert_eq!(finder >> 12, data, "version {}", symbol.version);
+            if let Some(&published) = VERSION_INFORMATION.get((data as usize).wrapping_sub(7)) {
+                assert_eq!(finder, published, "version {}", symbol.version);
+            }
+            codewords.push(finder);
+        }
+
+        //the ones outside of the table still differ from each other in at least 8 bits
+        for (idx, a) in codewords.iter().enumerate() {
+            for b in &codewords[idx + 1..] {
+                assert!((a ^ b).count_ones() >= 8, "{a:#07X} and {b:#07X}");
+            }
+        }
+    }
+
+    #[test]
+    fn function_patterns() {
+        for symbol in blank_symbols() {
+            let (width, height) = (symbol.width(), symbol.height());
+            let dark = |x, y| symbol.is_dark(x, y);
+
+            //finder: a 7x7 ring around a 3x3 square, then a light separator where the symbol is high enough
+            for y in 0..7 {
+                for x in 0..7 {
+                    let ring = x == 0 || x == 6 || y == 0 || y == 6;
+                    let centre = (2..=4).contains(&x) && (2..=4).contains(&y);
+                    assert_eq!(
+                        dark(x, y),
+                        ring || centre,
+                        "({x}, {y}) of R{height}x{width}"
+                    );
+                }
+                assert!(!dark(7, y));
+            }
+            if height > 7 {
+                assert!((0..8).all(|x| !dark(x, 7)));
+            }
+
+            //sub-finder: a 5x5 ring around a single module in the bottom right corner
+            for dy in 0..5 {
+                for dx in 0..5 {
+                    let ring = dx == 0 || dx == 4 || dy == 0 || dy == 4;
+                    let centre = dx == 2 && dy == 2;
+                    let (x, y) = (width - 5 + dx, height - 5 + dy);
+                    assert_eq!(
+                        dark(x, y),
+                        ring || centre,
+                        "({x}, {y}) of R{height}x{width}"
+                    );
+                }
+            }
+
+            //corner patterns of the top right and (but for R7 and R9, where the finder and its separator
+            //take it) bottom left
+            assert!(
+                dark(width - 1, 0)
+                    && dark(width - 2, 0)
+                    && dark(width - 1, 1)
+                    && !dark(width - 2, 1)
+            );
+            if height > 9 {
+                assert!(dark(0, height - 1) && dark(1, height - 1) && dark(0, height - 2));
+                assert!(!dark(1, height - 2));
+            }
+
+            //timing patterns along the top and bottom edges, outside the finder, sub-finder and alignment patterns
+            let alignment = obtain_rmqr_alignment(width);
+            let timing =
+                (8..width - 5).filter(|&x| alignment.iter().all(|&column| x.abs_diff(column) > 1));
+            for x in timing {
+                assert_eq!(dark(x, 0), x % 2 == 0);
+                assert_eq!(dark(x, height - 1), x % 2 == 0);
+            }
+
+            //3x3 alignment patterns at both ends of each column, joined by a timing pattern
+            assert_eq!(alignment.is_empty(), width == 27);
+            for &column in alignment {
+                for dy in 0..3 {
+                    for dx in 0..3 {
+                        let ring = dx != 1 || dy != 1;
+                        assert_eq!(dark(column + dx - 1, dy), ring);
+                        assert_eq!(dark(column + dx - 1, height - 1 - dy), ring);
+                    }
+                }
+                assert!((0..height).all(|y| dark(column, y) == (y % 2 == 0)));
+            }
+        }
+    }
+
+    #[test]
+    fn codeword_placement() {
+        //R11x27, the smallest symbol, holds a single block at M so its first data codeword is placed first
+        let symbol = encode("0123", ECCLevel::Medium).unwrap();
+        assert_eq!(
+            (symbol.version(), symbol.width(), symbol.height()),
+            (10, 27, 11)
+        );
+
+        let bits = symbol
+            .data_positions()
+            .map(|(x, y)| symbol.is_dark(x, y) != MASKS[RMQR_MASK](y, x))
+            .collect::<Vec<_>>();
+        //numeric mode indicator 001, 4 count bits (4), then 012 in 10 bits
+        let expected = [0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0];
+        let header = bits[..18].iter().map(|&bit| bit as u8).collect::<Vec<_>>();
+        assert_eq!(header, expected);
+
+        //the bits past the last codeword are left light
+        let (total, _, _) = rmqr_block(&ECCLevel::Medium, 10);
+        assert!(bits[8 * total..].iter().all(|&bit| !bit));
+    }
+
+    #[test]
+    fn version_selection() {
+        //numeric mode bits of `len` digits in `version`, `None` if the count does not fit
+        let bits = |len: usize, version: u8| {
+            let count_bits = rmqr_count_bits(version)[0];
+            (len < 1 << count_bits).then(|| 3 + count_bits + 10 * (len / 3) + [0, 4, 7][len % 3])
+        };
+        let area = |version: u8| {
+            let (height, width) = rmqr_size(version);
+            height * width
+        };
+
+        let mut used = [false; 32];
+        for ecc in [ECCLevel::Medium, ECCLevel::High] {
+            let fits = |len, version| {
+                bits(len, version).is_some_and(|bits| bits <= 8 * rmqr_block(&ecc, version).2)
+            };
+
+            //the most digits each version holds, and one more
+            let mut lens = (0..32)
+                .filter_map(|version| (1..512).filter(|&len| fits(len, version)).max())
+                .flat_map(|len| [len, len + 1])
+                .collect::<Vec<_>>();
+            lens.sort_unstable();
+            lens.dedup();
+
+            for len in lens {
+                let Ok(symbol) = encode(&"7".repeat(len), ecc) else {
+                    assert!((0..32).all(|version| !fits(len, version)), "{len} digits");
+                    continue;
+                };
+                let version = symbol.version();
+                used[version as usize] = true;
+                assert_eq!(symbol.ecc_level(), ecc);
+                assert!(fits(len, version), "{len} digits");
+                //no version of a smaller area holds them
+                assert!((0..32)
+                    .filter(|&other| area(other) < area(version))
+                    .all(|other| !fits(len, other)));
+            }
+        }
+
+        //R17x139 holds up to 361 digits at M, and most versions are the smallest for some length
+        assert!(used[31]);
+        assert!(encode(&"7".repeat(362), ECCLevel::Medium).is_err());
+        assert!(used.iter().filter(|&&used| used).count() > 20);
+    }
+
+    #[test]
+    fn levels_and_masks() {
+        assert_eq!(
+            encode("1", ECCLevel::Low).unwrap().ecc_level(),
+            ECCLevel::Medium
+        );
+        assert_eq!(
+            encode("1", ECCLevel::Quartile).unwrap().ecc_level(),
+            ECCLevel::High
+        );
+
+        let mut builder = QrOptions::builder();
+        builder.mask(2);
+        let pinned = Generator::new(String::from("1"), builder.build().unwrap()).run_rmqr();
+        assert!(matches!(pinned, Err(QrError::InvalidOption(_))));
+    }
+
+    #[test]
+    fn size_limits() {
+        let limited = |len: usize, height: Option<usize>, width: Option<usize>| {
+            let mut builder = QrOptions::builder();
+            builder.ecc(ECCLevel::Medium);
+            height.map(|modules| builder.max_height(modules));
+            width.map(|modules| builder.max_width(modules));
+            let options = builder.build()?;
+            Generator::new("7".repeat(len), options).run_rmqr()
+        };
+        let size = |symbol: Result<RMQRCode, QrError>| {
+            let symbol = symbol.unwrap();
+            (symbol.height(), symbol.width())
+        };
+
+        //40 digits take 143 bits or more, R11x43 holds 152 at M, R9x59 168 and R7x77 160
+        //but R9x43 only 96
+        assert_eq!(size(limited(40, None, None)), (11, 43));
+        assert_eq!(size(limited(40, Some(9), None)), (9, 59));
+        assert_eq!(size(limited(40, Some(7), None)), (7, 77));
+        assert_eq!(size(limited(40, Some(9), Some(59))), (9, 59));
+        assert!(matches!(
+            limited(40, Some(9), Some(43)),
+            Err(QrError::NoVersionFits)
+        ));
+        assert!(matches!(
+            limited(40, None, Some(27)),
+            Err(QrError::NoVersionFits)
+        ));
+
+        //60 digits take 209 bits, R7x99 holds 224
+        assert_eq!(size(limited(60, Some(7), None)), (7, 99));
+        assert!(matches!(
+            limited(60, Some(7), Some(77)),
+            Err(QrError::NoVersionFits)
+        ));
+
+        for (height, width) in [(Some(6), None), (None, Some(26))] {
+            assert!(matches!(
+                limited(40, height, width),
+                Err(QrError::InvalidOption(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,314 @@
+#[cfg(feature = "std")]
+use std::ops::Range;
+
+#[cfg(feature = "hanzi")]
+use super::encoder::encode_gb2312;
+#[cfg(feature = "kanji")]
+use super::encoder::encode_shift_jis;
+use super::encoder::{alphanum_value, is_hanzi, is_kanji};
+#[cfg(feature = "std")]
+use super::{
+    bitstream::BitStream,
+    charset::Charset,
+    encoder::{
+        encode_header, AlphanumEncoder, BytesEncoder, Encoder, HanziEncoder, KanjiEncoder,
+        NumeralEncoder,
+    },
+};
+#[cfg(feature = "std")]
+use crate::error::QrError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Numeric,
+    Alphanumeric,
+    Byte,
+    Kanji,
+    /// GB2312 characters in the hanzi mode of the Chinese QR code standard, which not every scanner reads
+    Hanzi,
+}
+
+impl Mode {
+    /// every mode, in the order of `from_index`
+    #[cfg(feature = "std")]
+    pub(crate) const ALL: [Mode; 5] = [
+        Mode::Numeric,
+        Mode::Alphanumeric,
+        Mode::Byte,
+        Mode::Kanji,
+        Mode::Hanzi,
+    ];
+
+    /// the mode numbering used by the segmentation
+    /// (0 = numeric, 1 = alphanumeric, 2 = byte, 3 = kanji, 4 = hanzi)
+    #[cfg(feature = "std")]
+    pub(crate) fn from_index(mode: u8) -> Result<Self, QrError> {
+        match mode {
+            0 => Ok(Mode::Numeric),
+            1 => Ok(Mode::Alphanumeric),
+            2 => Ok(Mode::Byte),
+            3 => Ok(Mode::Kanji),
+            4 => Ok(Mode::Hanzi),
+            _ => Err(QrError::InvalidMode(mode)),
+        }
+    }
+
+    /// true if `ch` can be written in this mode
+    pub fn accepts(self, ch: char) -> bool {
+        match self {
+            Mode::Numeric => ch.is_ascii_digit(),
+            Mode::Alphanumeric => alphanum_value(ch).is_some(),
+            Mode::Byte => true,
+            Mode::Kanji => is_kanji(ch),
+            Mode::Hanzi => is_hanzi(ch),
+        }
+    }
+
+    /// size of the character count indicator
+    pub(crate) fn count_bits(self, version: u8) -> usize {
+        let class = if version <= 9 {
+            0
+        } else if version <= 26 {
+            1
+        } else {
+            2
+        };
+
+        match self {
+            Mode::Numeric => [10, 12, 14][class],
+            Mode::Alphanumeric => [9, 11, 13][class],
+            Mode::Byte => [8, 16, 16][class],
+            Mode::Kanji | Mode::Hanzi => [8, 10, 12][class],
+        }
+    }
+
+    /// size of the mode indicator, the hanzi subset indicator and the character count indicator
+    pub(crate) fn header_bits(self, version: u8) -> usize {
+        let subset = if self == Mode::Hanzi { 4 } else { 0 };
+        4 + subset + self.count_bits(version)
+    }
+
+    /// size of `len` characters (octets in byte mode), headers excluded
+    pub(crate) fn data_bits(self, len: usize) -> usize {
+        match self {
+            Mode::Numeric => 10 * (len / 3) + [0, 4, 7][len % 3],
+            Mode::Alphanumeric => 11 * (len / 2) + 6 * (len % 2),
+            Mode::Byte => 8 * len,
+            Mode::Kanji | Mode::Hanzi => 13 * len,
+        }
+    }
+}
+
+/// a run of data written in a single mode, either planned by the generator or built by hand
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    mode: Mode,
+    data: Vec<u8>,
+    //raw octets rather than UTF-8 text, only possible in byte mode
+    binary: bool,
+}
+
+#[cfg(feature = "std")]
+impl Segment {
+    /// fails if any character of `text` cannot be written in `mode`
+    pub fn new(mode: Mode, text: &str) -> Result<Self, QrError> {
+        if let Some(ch) = text.chars().find(|&ch| !mode.accepts(ch)) {
+            return Err(QrError::UnencodableCharacter { ch, mode });
+        }
+
+        Ok(Self {
+            mode,
+            data: text.as_bytes().to_vec(),
+            binary: false,
+        })
+    }
+
+    pub fn numeric(text: &str) -> Result<Self, QrError> {
+        Self::new(Mode::Numeric, text)
+    }
+
+    pub fn alphanumeric(text: &str) -> Result<Self, QrError> {
+        Self::new(Mode::Alphanumeric, text)
+    }
+
+    pub fn kanji(text: &str) -> Result<Self, QrError> {
+        Self::new(Mode::Kanji, text)
+    }
+
+    pub fn hanzi(text: &str) -> Result<Self, QrError> {
+        Self::new(Mode::Hanzi, text)
+    }
+
+    /// text in byte mode
+    pub fn bytes(text: &str) -> Self {
+        Self {
+            mode: Mode::Byte,
+            data: text.as_bytes().to_vec(),
+            binary: false,
+        }
+    }
+
+    /// octets in byte mode, written as they are
+    pub fn binary(data: &[u8]) -> Self {
+        Self {
+            mode: Mode::Byte,
+            data: data.to_vec(),
+            binary: true,
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// the text of the segment, `None` for binary segments
+    pub fn text(&self) -> Option<&str> {
+        if self.binary {
+            None
+        } else {
+            std::str::from_utf8(&self.data).ok()
+        }
+    }
+
+    /// the octets of a binary segment or the UTF-8 of a text segment
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// the number of characters as counted by the character count indicator
+    /// (byte mode text is counted in UTF-8 octets, fewer if the symbol is written in Latin-1)
+    pub fn char_count(&self) -> usize {
+        match (self.mode, self.text()) {
+            (Mode::Kanji | Mode::Hanzi, Some(text)) => text.chars().count(),
+            _ => self.data.len(),
+        }
+    }
+
+    //the character count, byte mode text being counted in octets of `charset`
+    pub(crate) fn count(&self, charset: Charset) -> usize {
+        match (self.mode, self.text()) {
+            (Mode::Byte, Some(text)) => charset.encoded_len(text),
+            _ => self.char_count(),
+        }
+    }
+
+    /// number of characters, octets for binary segments
+    pub(crate) fn len(&self) -> usize {
+        match self.text() {
+            Some(text) => text.chars().count(),
+            None => self.data.len(),
+        }
+    }
+
+    /// the characters (octets for binary segments) in `range`
+    pub(crate) fn slice(&self, range: Range<usize>) -> Self {
+        let data = match self.text() {
+            Some(text) => text
+                .chars()
+                .skip(range.start)
+                .take(range.len())
+                .collect::<String>()
+                .into_bytes(),
+            None => self.data[range].to_vec(),
+        };
+
+        Self {
+            mode: self.mode,
+            data,
+            binary: self.binary,
+        }
+    }
+
+    /// XOR of the octets the segment holds (byte mode text in `charset`, kanji in Shift JIS, hanzi in GB2312),
+    /// the parity of structured append
+    pub(crate) fn parity(&self, charset: Charset) -> Result<u8, QrError> {
+        let octets = match (self.mode, self.text()) {
+            (Mode::Byte, Some(text)) => charset.encode(text)?,
+            #[cfg(feature = "kanji")]
+            (Mode::Kanji, Some(text)) => encode_shift_jis(text).ok_or(QrError::UnencodableKanji)?,
+            #[cfg(feature = "hanzi")]
+            (Mode::Hanzi, Some(text)) => {
+                encode_gb2312(text).ok_or_else(|| QrError::UnencodableCharacter {
+                    ch: text.chars().find(|&ch| !is_hanzi(ch)).unwrap_or_default(),
+                    mode: Mode::Hanzi,
+                })?
+            }
+            _ => self.data.clone(),
+        };
+
+        Ok(octets.iter().fold(0, |parity, byte| parity ^ byte))
+    }
+
+    /// size of the segment in bits, headers included
+    pub(crate) fn bits(&self, version: u8, charset: Charset) -> usize {
+        self.mode.header_bits(version) + self.mode.data_bits(self.count(charset))
+    }
+
+    pub(crate) fn encode(
+        &self,
+        stream: &mut BitStream,
+        version: u8,
+        charset: Charset,
+    ) -> Result<(), QrError> {
+        encode_header(self.mode, self.count(charset), stream, version);
+        self.encode_data(stream, charset)
+    }
+
+    /// writes the characters of the segment, the symbol writing the header the way it numbers modes
+    pub(crate) fn encode_data(
+        &self,
+        stream: &mut BitStream,
+        charset: Charset,
+    ) -> Result<(), QrError> {
+        let len = self.char_count();
+        let text = match self.text() {
+            Some(text) => text,
+            None => {
+                BytesEncoder::encode_raw(&self.data, stream);
+                return Ok(());
+            }
+        };
+
+        let mut chars = text.chars();
+        match self.mode {
+            Mode::Numeric => NumeralEncoder::encode(&mut chars, len, stream),
+            Mode::Alphanumeric => AlphanumEncoder::encode(&mut chars, len, stream),
+            Mode::Byte if charset == Charset::Utf8 => {
+                BytesEncoder::encode(&mut chars, text.chars().count(), stream)
+            }
+            Mode::Byte => {
+                BytesEncoder::encode_raw(&charset.encode(text)?, stream);
+                Ok(())
+            }
+            Mode::Kanji => KanjiEncoder::encode(&mut chars, len, stream),
+            Mode::Hanzi => HanziEncoder::encode(&mut chars, len, stream),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "hanzi"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hanzi_parity() {
+        //汉 and 字 are BA BA and D7 D6 in GB2312
+        let segment = Segment::hanzi("汉字").unwrap();
+        assert_eq!(segment.parity(Charset::Latin1).unwrap(), 0xD7 ^ 0xD6);
+
+        //漢 is not in GB2312, only a segment built by hand can hold it
+        let segment = Segment {
+            mode: Mode::Hanzi,
+            data: "汉漢".as_bytes().to_vec(),
+            binary: false,
+        };
+        assert!(matches!(
+            segment.parity(Charset::Latin1),
+            Err(QrError::UnencodableCharacter {
+                ch: '漢',
+                mode: Mode::Hanzi
+            })
+        ));
+    }
+}
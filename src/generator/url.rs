@@ -0,0 +1,135 @@
+use super::{Generator, QrOptions};
+
+//the length of the scheme and host of `uri`, `None` if it is not an http or https URI
+pub(crate) fn authority_len(uri: &str) -> Option<usize> {
+    let lower = uri.to_ascii_lowercase();
+    let rest = lower
+        .strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))?;
+    let host = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    (host > 0).then_some(uri.len() - rest.len() + host)
+}
+
+/// the variant `uri` builds that gives the smallest symbol with `options`,
+/// each of its `len` flags being set in turn and kept only if it saves bits
+pub(crate) fn smallest(len: usize, options: &QrOptions, uri: impl Fn(&[bool]) -> String) -> String {
+    let size = |uri: String| {
+        Generator::new(uri, options.clone())
+            .plan()
+            .map_or((u8::MAX, usize::MAX), |(preflight, _)| {
+                (preflight.version(), preflight.bits())
+            })
+    };
+
+    let mut flags = vec![false; len];
+    let mut best = size(uri(&flags));
+    for idx in 0..len {
+        flags[idx] = true;
+        let encoded = size(uri(&flags));
+        if encoded < best {
+            best = encoded;
+        } else {
+            flags[idx] = false;
+        }
+    }
+
+    uri(&flags)
+}
+
+//percent-encodes the unreserved characters alphanumeric mode cannot hold, which leaves the URI
+//equivalent (RFC 3986), and writes the hex digits of the octets already percent-encoded in uppercase
+//
+//a part with a `%` that is not followed by two hex digits is left as it is, it is not an escape
+fn encode_unreserved(part: &str, encode: bool) -> String {
+    let bytes = part.as_bytes();
+    let stray = bytes.iter().enumerate().any(|(idx, &byte)| {
+        byte == b'%'
+            && !bytes
+                .get(idx + 1..idx + 3)
+                .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+    });
+    if stray {
+        return String::from(part);
+    }
+
+    let mut encoded = String::new();
+    let mut hex = 0;
+    for ch in part.chars() {
+        if hex > 0 {
+            hex -= 1;
+            encoded.push(ch.to_ascii_uppercase());
+        } else if ch == '%' {
+            hex = 2;
+            encoded.push(ch);
+        } else if encode && (ch.is_ascii_lowercase() || ch == '_' || ch == '~') {
+            encoded.push_str(&format!("%{:02X}", ch as u32));
+        } else {
+            encoded.push(ch);
+        }
+    }
+
+    encoded
+}
+
+/// the form of `url` that gives the smallest symbol with `options`: the scheme and host in
+/// uppercase, as they are case insensitive, and the parts of the path, query and fragment
+/// percent-encoded wherever that moves them to alphanumeric mode and saves bits.
+/// Anything but an http or https URL is returned as it is
+pub fn compact_url(url: &str, options: &QrOptions) -> String {
+    let Some(len) = authority_len(url) else {
+        return String::from(url);
+    };
+
+    //the user information before the host keeps its case
+    let mut authority = String::from(&url[..len]);
+    let scheme = authority.find("://").unwrap_or_default();
+    let host = authority.rfind('@').map_or(scheme + 3, |at| at + 1);
+    authority[..scheme].make_ascii_uppercase();
+    authority[host..].make_ascii_uppercase();
+
+    let parts = url[len..]
+        .split_inclusive(['/', '?', '#', '&', '=', ';'])
+        .collect::<Vec<_>>();
+    smallest(parts.len(), options, |encoded| {
+        let mut url = authority.clone();
+        for (part, &encode) in parts.iter().zip(encoded) {
+            url.push_str(&encode_unreserved(part, encode));
+        }
+
+        url
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compact(url: &str) -> String {
+        compact_url(url, &QrOptions::builder().build().unwrap())
+    }
+
+    #[test]
+    fn stray_percent_is_left_as_it_is() {
+        let url = compact("https://example.com/50%off-sale");
+        assert_eq!(url, "HTTPS://EXAMPLE.COM/50%off-sale");
+    }
+
+    #[test]
+    fn escapes_are_uppercased() {
+        let url = compact("https://example.com/a%2fb");
+        assert!(url.starts_with("HTTPS://EXAMPLE.COM/"));
+        assert!(url.contains("%2F"));
+        assert!(!url.contains("%2f"));
+    }
+
+    #[test]
+    fn user_information_keeps_its_case() {
+        let url = compact("http://user:Pw@shop.example.com:8080/item");
+        assert!(url.starts_with("HTTP://user:Pw@SHOP.EXAMPLE.COM:8080/"));
+    }
+
+    #[test]
+    fn other_schemes_are_left_as_they_are() {
+        assert_eq!(compact("ftp://example.com/file"), "ftp://example.com/file");
+    }
+}
@@ -0,0 +1,67 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod error;
+pub mod generator;
+#[cfg(feature = "scanner")]
+pub mod scanner;
+
+pub use error::QrError;
+pub use generator::{
+    codeword_buffer_len, encode_into, matrix_buffer_len, ECCLevel, Mode, Module, ModuleKind,
+    Penalty, QrMatrix,
+};
+#[cfg(feature = "std")]
+pub use generator::{
+    Charset, DigitalLink, ElementString, Fnc1, Generator, MicroQRCode, Preflight, QRCode,
+    QrOptions, QrOptionsBuilder, RMQRCode, Segment,
+};
+
+#[cfg(feature = "std")]
+/// encodes `text` into a QR code symbol without touching the filesystem
+pub fn encode(text: &str, options: &QrOptions) -> Result<QRCode, QrError> {
+    Generator::new(String::from(text), options.clone()).run()
+}
+
+#[cfg(feature = "std")]
+/// encodes arbitrary octets (keys, compressed blobs, ...) into a single byte segment
+pub fn encode_bytes(data: &[u8], options: &QrOptions) -> Result<QRCode, QrError> {
+    Generator::from_bytes(data.to_vec(), options.clone()).run()
+}
+
+#[cfg(feature = "std")]
+/// encodes hand-built segments as they are, e.g. a byte mode prefix followed by a numeric serial
+pub fn encode_segments(segments: &[Segment], options: &QrOptions) -> Result<QRCode, QrError> {
+    Generator::from_segments(segments.to_vec(), options.clone()).run()
+}
+
+#[cfg(feature = "std")]
+/// encodes a GS1 element string such as `(01)09501101530003(17)251231(10)ABC` in FNC1 mode,
+/// failing if it is malformed or a data field is invalid
+pub fn encode_gs1(element_string: &str, options: &QrOptions) -> Result<QRCode, QrError> {
+    let data = ElementString::parse(element_string)?.data();
+    Generator::new(data, options.with_fnc1(Fnc1::First)).run()
+}
+
+#[cfg(feature = "std")]
+/// encodes `text` into one symbol, or into a structured append sequence of up to 16 if it does not fit one
+pub fn encode_structured(text: &str, options: &QrOptions) -> Result<Vec<QRCode>, QrError> {
+    Generator::new(String::from(text), options.clone()).run_structured()
+}
+
+#[cfg(feature = "std")]
+/// encodes `text` into the smallest Micro QR symbol (M1 to M4) holding it, for labels too small for a QR code
+pub fn encode_micro(text: &str, options: &QrOptions) -> Result<MicroQRCode, QrError> {
+    Generator::new(String::from(text), options.clone()).run_micro()
+}
+
+#[cfg(feature = "std")]
+/// encodes `text` into the rectangular Micro QR symbol (R7x43 to R17x139) of the smallest area holding it
+pub fn encode_rmqr(text: &str, options: &QrOptions) -> Result<RMQRCode, QrError> {
+    Generator::new(String::from(text), options.clone()).run_rmqr()
+}
+
+#[cfg(feature = "std")]
+/// the version `text` gets, the exact number of bits it takes and what room is left, without encoding it
+pub fn preflight(text: &str, options: &QrOptions) -> Result<Preflight, QrError> {
+    Generator::new(String::from(text), options.clone()).preflight()
+}
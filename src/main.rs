@@ -1,149 +1,457 @@
-mod generator;
-mod scanner;
-use std::process::exit;
+use std::{fs, process::exit};
 
-use scanner::Scanner;
-use generator::{ECCLevel, Flag, Generator};
+use qr_gen::{
+    generator::{compact_url, decode_base64, decode_hex, decode_input},
+    scanner::Scanner,
+    Charset, DigitalLink, ECCLevel, ElementString, Fnc1, Generator, MicroQRCode, QRCode, QrError,
+    QrOptions, QrOptionsBuilder, RMQRCode,
+};
+
+//where the data comes from
+#[derive(Clone, Copy, PartialEq)]
+enum Input {
+    Argument,
+    File,
+    //a data file in another encoding than UTF-8
+    Encoded(Charset),
+}
+
+//how the text argument (or the data file contents) is interpreted
+#[derive(PartialEq)]
+enum Literal {
+    Text,
+    Hex,
+    Base64,
+    Gs1,
+    //the GS1 Digital Link URI on the resolver domain
+    DigitalLink(String),
+    //an http or https URL, rewritten for the smallest symbol
+    Url,
+}
+
+//the symbols the data is encoded into
+#[derive(PartialEq)]
+enum Symbol {
+    Single,
+    //a structured append sequence if the data does not fit one symbol
+    Structured,
+    Micro,
+    Rmqr,
+}
 
 fn usage_str() -> String {
     String::from("
 Usage: qr-gen [OPTIONS (optional)] <text OR data file path> [generated-image-path (default: qr_code.png)] [pixel size (default: 5)]
+The generated path may also end in .json or .qrb to save the symbol itself (JSON or compact binary) instead of an image.
 Scanner Options:
     -s: switch mode to scanning the QR Code (format: qr-gen [OPTIONS] <qr code image file path> [generated-parsed-data-path (default: parsed.txt)])
 
 Generator Options:
-    -f: data file path is provided | (default is false)
-	-b: convert data to bytes, encoding types (eci headers) will not be considered | (default is false)
+    -f: data file path is provided, files that are not UTF-8 are encoded as raw bytes (see -i) | (default is false)
+	-i[encoding]: the data file (implies -f) is written in 'encoding' rather than UTF-8, a UTF-8 or UTF-16 byte order mark overrides it. (eg: -ishift_jis)
+	    the octets are kept as they are when -c names the same charset, takes the same names as -c
+	-r: the data file is a symbol saved as .json or .qrb, it is rendered as it is without encoding anything
+	-hex: the text (or data file) is a hex literal of the bytes to encode (eg: -hex DEADBEEF)
+	-base64: the text (or data file) is a base64 literal of the bytes to encode
+	-gs1: the text (or data file) is a GS1 element string, validated and encoded in FNC1 mode, parentheses in a data field are written \\( and \\) (eg: -gs1 (01)09501101530003(10)ABC)
+	-dl[domain]: the text (or data file) is a GS1 element string, encoded and printed as a GS1 Digital Link URI on the resolver 'domain' (eg: -dlhttps://id.gs1.org)
+	-url: the text (or data file) is an http or https URL, the scheme and host are uppercased and parts percent-encoded where that gives a smaller symbol, the URL and the versions before and after are printed
+	-a: split data that does not fit a single QR code into up to 16 (structured append), saved as numbered files (eg: qr_code-1.png)
+	-micro: encode into a Micro QR symbol (M1 to M4, Latin-1 text and kanji only, no level H), saved as an image only
+	-rmqr: encode into a rectangular Micro QR symbol (R7x43 to R17x139, Latin-1 text and kanji only, levels M and H), saved as an image only
+	-H[number]: maximum height of the rMQR symbol in modules, from 7 to 17, the smallest symbol no higher is chosen. (eg: -H9) | (default is 17)
+	-W[number]: maximum width of the rMQR symbol in modules, from 27 to 139. (eg: -W59) | (default is 139)
+	-b: write the whole data as a single byte segment instead of splitting it into numeric, alphanumeric and kanji segments, the text is still written in the charset of -c (or the default one) with its ECI | (default is false)
+	-hanzi: write GB2312 Chinese characters in hanzi mode (13 bits each instead of 24 in UTF-8), not every scanner reads it | (default is false)
+	-c[charset]: charset of the text in byte mode, announced with an ECI. (eg: -cshift_jis) | (default is ISO-8859-1 if every character fits, else UTF-8)
+	    utf-8, iso-8859-1 to iso-8859-16 (no 12), shift_jis, gb18030, big5, euc-kr, windows-1250, windows-1251, windows-1252
 	-v[number]: minimum version of QR code being version 'number' from 1 to 40. (eg: -v3) | (default is 1)
+	-V[number]: maximum version of QR code, use together with -v for an exact version. (eg: -V10) | (default is 40)
+	-m[number]: force mask pattern 'number' from 0 to 7 (0 to 3 for Micro QR, rMQR has a single mask) instead of choosing the least penalty. (eg: -m3)
+	-q[number]: width of the quiet zone in modules. (eg: -q2) | (default is 4, 2 for Micro QR and rMQR)
 	-e[number]: error correction, takes up about ~x% space. 0 = low (~7%), 1 = medium (~15%), 2 = quartile (~25%), 3 = high (~30%) | (default is quartile)
+	-boost: raise the error correction level as far as the data still fits the chosen version, the level used is printed | (default is false)
+
+Exit Codes:
+    0: success, 2: invalid option, 3: input cannot be read, 4: output cannot be written,
+    5: no characters, 6: too many characters, 7: no version fits, 8: invalid numerals,
+    9: unencodable kanji, 10: invalid encoding mode, 11: image cannot be decoded,
+    12: no QR code found, 13: QR code cannot be parsed, 14: invalid hex or base64 literal or input encoding,
+    15: character does not fit its segment mode, 16: malformed .json or .qrb symbol,
+    18: character not in the charset, 19: data exceeds the symbol capacity,
+    20: invalid GS1 element string
 ")
 }
 
-fn set_options(op: &String, flag: &mut Flag, scan: &mut bool) {
-    match op.as_str() {
+fn flag_number(num: &str, name: &str, example: &str) -> Result<u32, QrError> {
+    if num.is_empty() {
+        return Err(QrError::InvalidOption(format!(
+            "{} must be provided. (eg: {})",
+            name, example
+        )));
+    }
+
+    num.parse::<u32>().map_err(|_| {
+        QrError::InvalidOption(format!("{} must be integer but given '{}'.", name, num))
+    })
+}
+
+fn set_options(
+    op: &str,
+    builder: &mut QrOptionsBuilder,
+    input: &mut Input,
+    literal: &mut Literal,
+    scan: &mut bool,
+    render: &mut bool,
+    symbol: &mut Symbol,
+) -> Result<(), QrError> {
+    match op {
         "-s" => *scan = true,
-        "-f" => flag.data = true,
-        "-b" => flag.bytes = true,
+        "-f" => {
+            if *input == Input::Argument {
+                *input = Input::File;
+            }
+        }
+        "-r" => *render = true,
+        "-a" => *symbol = Symbol::Structured,
+        "-micro" => *symbol = Symbol::Micro,
+        "-rmqr" => *symbol = Symbol::Rmqr,
+        "-hex" => *literal = Literal::Hex,
+        "-base64" => *literal = Literal::Base64,
+        "-url" => *literal = Literal::Url,
+        "-gs1" => {
+            *literal = Literal::Gs1;
+            builder.fnc1(Fnc1::First);
+        }
+        "-b" => {
+            builder.force_bytes(true);
+        }
+        "-hanzi" => {
+            builder.hanzi(true);
+        }
+        "-boost" => {
+            builder.boost_ecc(true);
+        }
         _ => {
-            if op.starts_with("-v") {
-                let num = String::from(op).split_off(2);
+            if let Some(num) = op.strip_prefix("-v") {
+                let x = flag_number(num, "minimum version", "-v1")?;
+                builder.min_version(x.min(u8::MAX as u32) as u8);
+            } else if let Some(num) = op.strip_prefix("-V") {
+                let x = flag_number(num, "maximum version", "-V10")?;
+                builder.max_version(x.min(u8::MAX as u32) as u8);
+            } else if let Some(num) = op.strip_prefix("-H") {
+                let x = flag_number(num, "maximum rMQR height", "-H9")?;
+                builder.max_height(x as usize);
+            } else if let Some(num) = op.strip_prefix("-W") {
+                let x = flag_number(num, "maximum rMQR width", "-W59")?;
+                builder.max_width(x as usize);
+            } else if let Some(num) = op.strip_prefix("-m") {
+                let x = flag_number(num, "mask", "-m3")?;
+                builder.mask(x.min(u8::MAX as u32) as u8);
+            } else if let Some(name) = op.strip_prefix("-c") {
+                let charset = Charset::from_name(name).ok_or_else(|| {
+                    QrError::InvalidOption(format!("unknown charset '{}'. (eg: -cutf-8)", name))
+                })?;
+                builder.charset(charset);
+            } else if let Some(name) = op.strip_prefix("-i") {
+                let encoding = Charset::from_name(name).ok_or_else(|| {
+                    QrError::InvalidOption(format!(
+                        "unknown encoding '{}'. (eg: -ishift_jis)",
+                        name
+                    ))
+                })?;
+                *input = Input::Encoded(encoding);
+            } else if let Some(domain) = op.strip_prefix("-dl") {
+                *literal = Literal::DigitalLink(String::from(domain));
+            } else if let Some(num) = op.strip_prefix("-q") {
+                builder.quiet_zone(flag_number(num, "quiet zone", "-q4")?);
+            } else if let Some(num) = op.strip_prefix("-e") {
                 if num.is_empty() {
-                    println!("Error: minimum version must be provided. (eg: -v1)");
-                    exit(0);
+                    return Err(QrError::InvalidOption(String::from(
+                        "error correction configuration must be provided. (eg: -e2)",
+                    )));
                 }
 
-                match num.parse::<u32>() {
-                    Ok(x) => {
-                        if x > 0 && x <= 40 {
-                            flag.min_vers = x as u8;
-                        } else {
-                            println!(
-                                "Error: minimum version must be 1 to 40 inclusive but given '{}'.",
-                                num
-                            );
-                            exit(0);
-                        }
-                    }
-                    Err(_) => {
-                        println!(
-                            "Error: minimum version must be integer but given '{}'.",
-                            num
-                        );
-                        exit(0);
+                builder.ecc(match num.parse::<u32>() {
+                    Ok(0) => ECCLevel::Low,
+                    Ok(1) => ECCLevel::Medium,
+                    Ok(2) => ECCLevel::Quartile,
+                    Ok(3) => ECCLevel::High,
+                    _ => {
+                        return Err(QrError::InvalidOption(String::from(
+                            "use -h to see how to use the '-e' flag.",
+                        )));
                     }
-                }
-            } else if op.starts_with("-e") {
-                let num = String::from(op).split_off(2);
-                if num.is_empty() {
-                    println!("Error: error correction configuration must be provided. (eg: -e2)");
-                    exit(0);
-                }
-
-                match num.parse::<u32>() {
-                    Ok(x) => {
-                        if x <= 3 {
-                            flag.ecc = match x {
-                                0 => ECCLevel::Low,
-                                1 => ECCLevel::Medium,
-                                2 => ECCLevel::Quartile,
-                                3 => ECCLevel::High,
-                                _ => ECCLevel::Quartile,
-                            }
-                        } else {
-                            println!("Error: use -h to see how to use the '-e' flag.");
-                            exit(0);
-                        }
-                    }
-                    Err(_) => {
-                        println!("Error: use -h to see how to use the '-e' flag.");
-                        exit(0);
-                    }
-                }
+                });
             } else {
-                println!("Unknown flag: {}", op);
-                exit(0);
+                return Err(QrError::InvalidOption(format!("unknown flag: {}", op)));
             }
         }
     }
+
+    Ok(())
 }
 
-fn main() {
-    let args = std::env::args().collect::<Vec<String>>();
+fn run(args: &[String]) -> Result<(), QrError> {
+    if args.len() <= 1 {
+        return Err(QrError::InvalidOption(String::from("use -h to see help.")));
+    }
 
-    if args.len() > 1 {
-        if args[1] == "-h" {
-            println!("{}", usage_str());
+    if args[1] == "-h" {
+        println!("{}", usage_str());
+        return Ok(());
+    }
+
+    let mut idx = 1;
+    let mut builder = QrOptions::builder();
+    let mut input = Input::Argument;
+    let mut literal = Literal::Text;
+    let mut scan = false;
+    let mut render = false;
+    let mut symbol = Symbol::Single;
+
+    while idx < args.len() && args[idx].starts_with("-") {
+        set_options(
+            &args[idx],
+            &mut builder,
+            &mut input,
+            &mut literal,
+            &mut scan,
+            &mut render,
+            &mut symbol,
+        )?;
+        idx += 1;
+    }
+
+    if idx >= args.len() {
+        return Err(QrError::InvalidOption(String::from(
+            "text or file path must be provided. (use -h to see help)",
+        )));
+    }
+
+    let text = args[idx].clone();
+    let path = if args.len() > idx + 1 {
+        args[idx + 1].clone()
+    } else if scan {
+        String::from("parsed.txt")
+    } else {
+        String::from("qr_code.png")
+    };
+    if args.len() > idx + 2 {
+        match args[idx + 2].parse::<u32>() {
+            Ok(x) => {
+                builder.scale(x);
+            }
+            Err(_) => {
+                return Err(QrError::InvalidOption(format!(
+                    "pixel size must be integer but given '{}'.",
+                    args[idx + 2]
+                )));
+            }
+        }
+    }
+    let options = builder.build()?;
+
+    if !(scan
+        || [".png", ".jpg", ".jpeg", ".json", ".qrb"]
+            .iter()
+            .any(|ext| path.ends_with(ext)))
+    {
+        return Err(QrError::InvalidOption(format!(
+            "unsupported file extension for output path. ({})",
+            path
+        )));
+    }
+
+    if scan {
+        let scan = Scanner::new(text, path.clone());
+        scan.scan()?;
+        println!("QR code(s) parsed successfully, written into {}.", path);
+    } else if render {
+        let input = fs::read(&text).map_err(QrError::ReadFailure)?;
+        let qr_code = if text.ends_with(".json") {
+            let json = String::from_utf8(input)
+                .map_err(|_| QrError::InvalidSymbol(String::from("JSON must be UTF-8.")))?;
+            QRCode::from_json(&json)?
+        } else {
+            QRCode::from_binary(&input)?
+        };
+
+        write_symbol(&qr_code, &options, &path)?;
+    } else {
+        let data = if input == Input::Argument {
+            text.into_bytes()
         } else {
-            let mut idx = 1;
-            let mut flag = Flag::new();
-            let mut scan = false;
+            fs::read(&text).map_err(QrError::ReadFailure)?
+        };
 
-            while args[idx].starts_with("-") {
-                set_options(&args[idx], &mut flag, &mut scan);
-                idx += 1;
+        let generator = match input {
+            Input::Encoded(encoding) if literal == Literal::Text => {
+                Generator::from_encoded(data, encoding, options.clone())?
             }
+            _ => {
+                let text = match input {
+                    Input::Encoded(encoding) => Ok(decode_input(&data, encoding)?),
+                    _ => String::from_utf8(data),
+                };
 
-            let text = args[idx].clone();
-            let path = if args.len() > idx + 1 {
-                args[idx + 1].clone()
-            } else {
-                if scan {
-                    String::from("parsed.txt")
-                } else {
-                    String::from("qr_code.png")
-                }
-            };
-            let size = if args.len() > idx + 2 {
-                match args[idx + 2].parse::<u32>() {
-                    Ok(x) => x,
+                match text {
+                    Ok(text) => match literal {
+                        Literal::Text => Generator::new(text, options.clone()),
+                        Literal::Hex => Generator::from_bytes(decode_hex(&text)?, options.clone()),
+                        Literal::Base64 => {
+                            Generator::from_bytes(decode_base64(&text)?, options.clone())
+                        }
+                        Literal::Gs1 => {
+                            let data = ElementString::parse(text.trim_end())?.data();
+                            Generator::new(data, options.clone())
+                        }
+                        Literal::DigitalLink(domain) => {
+                            let element_string = ElementString::parse(text.trim_end())?;
+                            let uri = DigitalLink::new(&domain, &element_string)?.compact(&options);
+                            println!("{}", uri);
+                            Generator::new(uri, options.clone())
+                        }
+                        Literal::Url => {
+                            let url = text.trim_end();
+                            let compact = compact_url(url, &options);
+                            let version = |url: &str| {
+                                Generator::new(String::from(url), options.clone())
+                                    .preflight()
+                                    .map_or(String::from("none"), |preflight| {
+                                        preflight.version().to_string()
+                                    })
+                            };
+                            println!(
+                                "{} (version {} before, {} after)",
+                                compact,
+                                version(url),
+                                version(&compact)
+                            );
+                            Generator::new(compact, options.clone())
+                        }
+                    },
+                    Err(e) if literal == Literal::Text => {
+                        Generator::from_bytes(e.into_bytes(), options.clone())
+                    }
                     Err(_) => {
-                        println!(
-                            "Error: pixel size must be integer but given '{}'.",
-                            args[idx + 2]
-                        );
-                        exit(0);
+                        return Err(QrError::InvalidInput(String::from(
+                            "hex, base64, GS1 and URL literals must be plain text.",
+                        )));
                     }
                 }
-            } else {
-                5
-            };
-
-            if !scan && (!(path.ends_with(".png") || path.ends_with(".jpg") || path.ends_with(".jpeg"))) {
-                eprintln!("Error: unsupported file extension for output path. ({})", path);
-                exit(0);
             }
+        };
 
-            //println!("{} {} {} {} {} {}", text, path, size, flag.data, flag.bytes, flag.min_vers);
-
-            if scan {
-                let scan = Scanner::new(text, path);
-                scan.scan();
-            } else {
-                let gen = Generator::new(text, path, size, flag);
-                gen.run();
+        if symbol == Symbol::Micro {
+            write_micro(&generator.run_micro()?, &options, &path)?;
+        } else if symbol == Symbol::Rmqr {
+            write_rmqr(&generator.run_rmqr()?, &options, &path)?;
+        } else if symbol == Symbol::Structured {
+            let qr_codes = generator.run_structured()?;
+            for (idx, qr_code) in qr_codes.iter().enumerate() {
+                let path = if qr_codes.len() > 1 {
+                    numbered(&path, idx + 1)
+                } else {
+                    path.clone()
+                };
+                write_symbol(qr_code, &options, &path)?;
             }
+        } else {
+            write_symbol(&generator.run()?, &options, &path)?;
         }
+    }
+
+    Ok(())
+}
+
+//"qr_code.png" becomes "qr_code-<number>.png"
+fn numbered(path: &str, number: usize) -> String {
+    let (stem, ext) = path.split_at(path.rfind('.').unwrap_or(path.len()));
+    format!("{}-{}{}", stem, number, ext)
+}
+
+//the image, or the symbol itself for .json and .qrb paths
+fn write_symbol(qr_code: &QRCode, options: &QrOptions, path: &str) -> Result<(), QrError> {
+    let written = if path.ends_with(".json") {
+        fs::write(path, qr_code.to_json(true)).map_err(|e| e.to_string())
+    } else if path.ends_with(".qrb") {
+        fs::write(path, qr_code.to_binary(true)).map_err(|e| e.to_string())
+    } else {
+        qr_code
+            .gen_image(options.scale(), options.quiet_zone())
+            .save(path)
+            .map_err(|e| e.to_string())
+    };
+    written.map_err(|e| QrError::WriteFailure(format!("{}: {}", path, e)))?;
+
+    if options.boost_ecc() {
+        println!(
+            "QR Code generated as '{}'. (version: {}, error correction: {:?})",
+            path,
+            qr_code.version(),
+            qr_code.ecc_level()
+        );
     } else {
-        println!("use -h to see help.");
+        println!(
+            "QR Code generated as '{}'. (version: {})",
+            path,
+            qr_code.version()
+        );
+    }
+    Ok(())
+}
+
+//Micro QR symbols have no .json or .qrb form, only the image
+fn write_micro(qr_code: &MicroQRCode, options: &QrOptions, path: &str) -> Result<(), QrError> {
+    if path.ends_with(".json") || path.ends_with(".qrb") {
+        return Err(QrError::InvalidOption(format!(
+            "Micro QR symbols can only be saved as images. ({})",
+            path
+        )));
+    }
+
+    qr_code
+        .gen_image(options.scale(), options.micro_quiet_zone())
+        .save(path)
+        .map_err(|e| QrError::WriteFailure(format!("{}: {}", path, e)))?;
+
+    println!(
+        "Micro QR Code generated as '{}'. (version: M{})",
+        path,
+        qr_code.version()
+    );
+    Ok(())
+}
+
+//rMQR symbols have no .json or .qrb form either
+fn write_rmqr(qr_code: &RMQRCode, options: &QrOptions, path: &str) -> Result<(), QrError> {
+    if path.ends_with(".json") || path.ends_with(".qrb") {
+        return Err(QrError::InvalidOption(format!(
+            "rMQR symbols can only be saved as images. ({})",
+            path
+        )));
+    }
+
+    qr_code
+        .gen_image(options.scale(), options.micro_quiet_zone())
+        .save(path)
+        .map_err(|e| QrError::WriteFailure(format!("{}: {}", path, e)))?;
+
+    println!(
+        "rMQR Code generated as '{}'. (version: R{}x{})",
+        path,
+        qr_code.height(),
+        qr_code.width()
+    );
+    Ok(())
+}
+
+fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+
+    if let Err(e) = run(&args) {
+        eprintln!("Error: {}", e);
+        exit(e.code());
     }
 }
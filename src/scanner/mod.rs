@@ -1,7 +1,9 @@
-use std::{fs::File, io::Write, path::Path, process::exit};
+use std::{fs::File, io, io::Write, path::Path};
 
 use image::ImageReader;
-use rqrr::PreparedImage;
+use rqrr::{BitGrid, Grid, PreparedImage};
+
+use crate::{error::QrError, generator, ECCLevel};
 
 #[derive(Debug)]
 pub struct Scanner {
@@ -17,54 +19,77 @@ impl Scanner {
         }
     }
 
-    pub fn scan(&self) {
+    /// parses every QR code in the input image into the output file, returning the number of codes parsed
+    ///
+    /// The symbols of a structured append sequence (see `Generator::run_structured`) are not read,
+    /// an image holding only those fails with `CodeUnreadable`.
+    pub fn scan(&self) -> Result<usize, QrError> {
         if !Path::new(&self.input).exists() {
-            eprintln!("Error: path does not exist.");
-            exit(0);
+            return Err(QrError::ReadFailure(io::Error::new(
+                io::ErrorKind::NotFound,
+                "path does not exist.",
+            )));
         }
 
         let img = ImageReader::open(&self.input)
-            .expect("Error: failed to open image.")
+            .map_err(QrError::ReadFailure)?
             .decode()
-            .map_err(|e| format!("Error: failed to decode image '{}'.", e))
-            .expect("Error: failed to decode image.")
+            .map_err(|e| QrError::ImageDecode(e.to_string()))?
             .to_luma8();
         let mut prep_img = PreparedImage::prepare(img);
         let grids = prep_img.detect_grids();
 
         if grids.is_empty() {
-            eprintln!("Error: QR code not found.");
-            exit(0);
+            return Err(QrError::NoCodeFound);
         }
 
-        let mut str = String::new();
+        //the text is written as UTF-8, the raw octets of binary payloads are kept so that they survive
+        //the round trip
+        let mut parsed = Vec::new();
         let mut idx = 0;
         for grid in grids {
-            match grid.decode() {
-                Ok((_meta_, content)) => {
-                    str.push_str(format!("Content #{} ---\n", idx).as_str());
-                    str.push_str(&content);
-                    idx += 1;
+            let mut content = Vec::new();
+            if grid.decode_to(&mut content).is_ok() {
+                if let Some(text) = symbol_text(&grid, &content) {
+                    content = text.into_bytes();
                 }
-                Err(_) => {}
+
+                parsed.extend_from_slice(format!("Content #{} ---\n", idx).as_bytes());
+                parsed.extend_from_slice(&content);
+                idx += 1;
             }
         }
 
         if idx == 0 {
-            eprintln!("Error: QR code cannot be parsed.");
-            exit(0);
+            return Err(QrError::CodeUnreadable);
         }
 
-        let mut file = File::create(&self.output).unwrap();
-        match file.write(str.as_bytes()) {
-            Ok(_) => {
-                println!("QR code(s) parsed successfully, written into {}.", self.output);
-            }
-            Err(x) => {
-                eprintln!("Error: parsed text cannot be written into {}", self.output);
-                eprintln!("Error: {}", x);
-                exit(0);
-            }
-        }
+        let mut file = File::create(&self.output)
+            .map_err(|e| QrError::WriteFailure(format!("{}: {}", self.output, e)))?;
+        file.write_all(&parsed)
+            .map_err(|e| QrError::WriteFailure(format!("{}: {}", self.output, e)))?;
+
+        Ok(idx)
     }
-}
\ No newline at end of file
+}
+
+//rqrr skips ECI headers, writes kanji as Shift JIS and byte segments as they are, so every symbol is
+//read again to decode its text, `None` for binary data (its octets are kept as they are)
+fn symbol_text<G: BitGrid>(grid: &Grid<G>, octets: &[u8]) -> Option<String> {
+    let (meta, raw) = grid.get_raw_data().ok()?;
+    //the two bits of the format information
+    let ecc = match meta.ecc_level {
+        0 => ECCLevel::Medium,
+        1 => ECCLevel::Low,
+        2 => ECCLevel::High,
+        _ => ECCLevel::Quartile,
+    };
+
+    generator::symbol_text(
+        meta.version.0 as u8,
+        ecc,
+        meta.mask as u8,
+        &raw.data[..raw.len.div_ceil(8)],
+        octets,
+    )
+}
@@ -0,0 +1,126 @@
+#![cfg(all(feature = "scanner", feature = "render-png"))]
+
+use std::{env, fs, path::PathBuf};
+
+use qr_gen::{
+    encode, encode_bytes, encode_structured, scanner::Scanner, Charset, Generator, QRCode, QrError,
+    QrOptions,
+};
+
+//saves the symbol, scans it back and returns what the scanner wrote for it
+fn scan(qr_code: &QRCode, name: &str) -> Vec<u8> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let image = dir.join(format!("{}.png", name));
+    let parsed = dir.join(format!("{}.txt", name));
+    qr_code.gen_image(4, 4).save(&image).unwrap();
+
+    let scanner = Scanner::new(image.display().to_string(), parsed.display().to_string());
+    assert_eq!(scanner.scan().unwrap(), 1);
+
+    let output = fs::read(parsed).unwrap();
+    let header = b"Content #0 ---\n";
+    assert!(output.starts_with(header));
+    output[header.len()..].to_vec()
+}
+
+fn scan_text(text: &str, charset: Option<Charset>, name: &str) -> String {
+    let mut builder = QrOptions::builder();
+    if let Some(charset) = charset {
+        builder.charset(charset);
+    }
+
+    let qr_code = encode(text, &builder.build().unwrap()).unwrap();
+    String::from_utf8(scan(&qr_code, name)).unwrap()
+}
+
+#[test]
+fn latin1_without_eci() {
+    assert_eq!(scan_text("Ünïcödé", None, "latin1"), "Ünïcödé");
+}
+
+#[test]
+fn binary_is_kept_as_it_is() {
+    let data = [0xDE, 0xAD, 0x00, 0xBE, 0xEF, 0x01, 0x7F, 0xFF];
+    let options = QrOptions::builder().build().unwrap();
+    let qr_code = encode_bytes(&data, &options).unwrap();
+    assert_eq!(scan(&qr_code, "binary"), data);
+}
+
+#[test]
+fn shift_jis_eci() {
+    let text = "ｶﾀｶﾅ ΑΒΓ №1";
+    assert_eq!(scan_text(text, Some(Charset::ShiftJis), "shift_jis"), text);
+}
+
+#[test]
+fn windows_1251_eci() {
+    let text = "Привет, мир!";
+    assert_eq!(
+        scan_text(text, Some(Charset::Windows1251), "windows_1251"),
+        text
+    );
+}
+
+#[test]
+fn unrepresentable_character() {
+    let options = QrOptions::builder()
+        .charset(Charset::Windows1251)
+        .build()
+        .unwrap();
+    assert!(matches!(
+        encode("Grüße", &options),
+        Err(QrError::UnrepresentableCharacter { ch: 'ü', .. })
+    ));
+
+    //encoded as the 0x5C of a backslash, which is what scanners read back
+    let options = QrOptions::builder()
+        .charset(Charset::ShiftJis)
+        .build()
+        .unwrap();
+    assert!(matches!(
+        encode("¥100", &options),
+        Err(QrError::UnrepresentableCharacter { ch: '¥', .. })
+    ));
+}
+
+#[test]
+fn structured_append_is_not_read() {
+    let options = QrOptions::builder().version(1).build().unwrap();
+    let parts = encode_structured(&"7".repeat(100), &options).unwrap();
+    assert!(parts.len() > 1);
+
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let image = dir.join("structured.png");
+    parts[0].gen_image(4, 4).save(&image).unwrap();
+    let parsed = dir.join("structured.txt").display().to_string();
+    let scanner = Scanner::new(image.display().to_string(), parsed);
+    assert!(matches!(scanner.scan(), Err(QrError::CodeUnreadable)));
+}
+
+#[test]
+fn encoded_data_file() {
+    //"Привет" saved as windows-1251, read with -iwindows-1251
+    let data = vec![0xCF, 0xF0, 0xE8, 0xE2, 0xE5, 0xF2];
+
+    //decoded and written in the default charset
+    let options = QrOptions::builder().build().unwrap();
+    let generator = Generator::from_encoded(data.clone(), Charset::Windows1251, options).unwrap();
+    let text = String::from_utf8(scan(&generator.run().unwrap(), "encoded")).unwrap();
+    assert_eq!(text, "Привет");
+
+    //kept as it is under the ECI of the same charset
+    let options = QrOptions::builder()
+        .charset(Charset::Windows1251)
+        .build()
+        .unwrap();
+    let generator = Generator::from_encoded(data, Charset::Windows1251, options).unwrap();
+    let text = String::from_utf8(scan(&generator.run().unwrap(), "encoded_eci")).unwrap();
+    assert_eq!(text, "Привет");
+
+    //not valid in the encoding it is read with
+    let options = QrOptions::builder().build().unwrap();
+    assert!(matches!(
+        Generator::from_encoded(vec![0x81], Charset::ShiftJis, options),
+        Err(QrError::InvalidInput(_))
+    ));
+}